@@ -35,6 +35,7 @@ pub fn app() -> Html {
     #[cfg(any(feature = "android",))]
     {
         use capacitor_bindings::status_bar::StatusBar;
+        use capacitor_bindings::status_bar::Animation;
         use capacitor_bindings::status_bar::Style;
         status_block = html! {
             <details>
@@ -46,8 +47,8 @@ pub fn app() -> Html {
                 <button onclick={|_| do_and_toast_result(||{StatusBar::set_style(Style::Light)})}> {"Set Status Light"}</button>
                 <button onclick={|_| do_and_toast_result(||{StatusBar::set_style(Style::Dark)})}> {"Set Status Dark"}</button>
                 <button onclick={|_| do_and_toast_result(||{StatusBar::set_background_color("#22DD44")})}> {"Set Status Background Green"}</button>
-                <button onclick={|_| do_and_toast_result(StatusBar::hide)}> {"Hide Status"}</button>
-                <button onclick={|_| do_and_toast_result(StatusBar::show)}> {"Show Status"}</button>
+                <button onclick={|_| do_and_toast_result(||{StatusBar::hide_with_options(Animation::Fade)})}> {"Hide Status"}</button>
+                <button onclick={|_| do_and_toast_result(||{StatusBar::show_with_options(Animation::Fade)})}> {"Show Status"}</button>
             </div>
             </details>
         };
@@ -55,6 +56,7 @@ pub fn app() -> Html {
     #[cfg(all(any(feature = "ios"), not(any(feature = "android"))))]
     {
         use capacitor_bindings::status_bar::StatusBar;
+        use capacitor_bindings::status_bar::Animation;
         use capacitor_bindings::status_bar::Style;
         status_block = html! {
             <details>
@@ -65,8 +67,8 @@ pub fn app() -> Html {
             <div style="display: flex; flex-direction: column;">
                 <button onclick={|_| do_and_toast_result(||{StatusBar::set_style(Style::Light)})}> {"Set Status Light"}</button>
                 <button onclick={|_| do_and_toast_result(||{StatusBar::set_style(Style::Dark)})}> {"Set Status Dark"}</button>
-                <button onclick={|_| do_and_toast_result(StatusBar::hide)}> {"Hide Status"}</button>
-                <button onclick={|_| do_and_toast_result(StatusBar::show)}> {"Show Status"}</button>
+                <button onclick={|_| do_and_toast_result(||{StatusBar::hide_with_options(Animation::Fade)})}> {"Hide Status"}</button>
+                <button onclick={|_| do_and_toast_result(||{StatusBar::show_with_options(Animation::Fade)})}> {"Show Status"}</button>
             </div>
             </details>
         };
@@ -280,23 +282,20 @@ fn request_permissions() {
 
 fn get_photo() {
     do_and_toast_result(|| {
-        Camera::get_photo(ImageOptions {
-            quality: 100,
-            allow_editing: true,
-            result_type: CameraResultType::Base64,
-            save_to_gallery: false,
-            width: 32,
-            height: 32,
-            correct_orientation: true,
-            source: CameraSource::Prompt,
-            direction: None,
-            presentation_style: None,
-            web_use_input: Some(false),
-            prompt_label_header: "Prompt Label Header".to_string(),
-            prompt_label_cancel: Some("Cancel".to_string()),
-            prompt_label_photo: Some("Select Saved Image".to_string()),
-            prompt_label_picture: Some("Open Camera".to_string()),
-        })
+        Camera::get_photo(
+            ImageOptions::builder()
+                .quality(100)
+                .allow_editing(true)
+                .result_type(CameraResultType::Base64)
+                .width(32)
+                .height(32)
+                .web_use_input(false)
+                .prompt_label_header("Prompt Label Header")
+                .prompt_label_cancel("Cancel")
+                .prompt_label_photo("Select Saved Image")
+                .prompt_label_picture("Open Camera")
+                .build(),
+        )
     })
 }
 
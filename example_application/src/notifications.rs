@@ -1,12 +1,11 @@
 use capacitor_bindings::error::Error;
 use capacitor_bindings::local_notifications::*;
 use capacitor_bindings::plugin_listener_handle::PluginListenerHandle;
-use chrono::{Datelike, Duration, Timelike};
+use capacitor_bindings::timestamp::Timestamp;
 use core::time;
 use log::info;
 use std::future::Future;
 use std::pin::Pin;
-use wasm_bindgen::JsValue;
 use yew::prelude::*;
 use yewdux::store::Store;
 
@@ -80,14 +79,9 @@ fn schedule_notifications() {
 
 fn schedule_a_notification() {
     do_and_toast_result(|| {
-        let time: chrono::DateTime<chrono::Utc> = chrono::Utc::now() + Duration::seconds(5); //notify 5 seconds from now
-        let time_string = time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
-        let at_millis = JsValue::from_f64(js_sys::Date::parse(&time_string));
-        let at = js_sys::Date::new(&at_millis);
+        let at = Timestamp::now() + std::time::Duration::from_secs(5); //notify 5 seconds from now
 
-        if let Some(at_str) = at.to_utc_string().as_string() {
-            info!("Scheduling notification at {at_str}")
-        }
+        info!("Scheduling notification at {at}");
 
         let options = LocalNotificationSchema::builder()
             .title("Notification Title 2")
@@ -119,19 +113,14 @@ fn register_action_types() {
     {
         do_and_toast_result(|| {
             let options = RegisterActionTypesOptions {
-                types: vec![ActionType {
-                    id: "MyActionType".to_string(),
-                    actions: vec![
-                        Action {
-                            id: "Foo".to_string(),
-                            title: "Foo".to_string(),
-                        },
-                        Action {
-                            id: "Bar".to_string(),
-                            title: "Bar".to_string(),
-                        },
+                types: vec![ActionType::new(
+                    "MyActionType",
+                    vec![
+                        Action::builder().id("Foo").title("Foo").build(),
+                        Action::builder().id("Bar").title("Bar").build(),
+                        Action::text_input("Reply", "Reply", "Send", "Type a reply"),
                     ],
-                }],
+                )],
             };
             LocalNotifications::register_action_types(options)
         });
@@ -1,24 +1,35 @@
 use std::collections::BTreeMap;
 
 use crate::extern_functions::*;
+use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use serde_with::skip_serializing_none;
+use typed_builder::TypedBuilder;
+use wasm_bindgen::JsCast;
 
+use crate::error::Error;
 use crate::helpers::*;
-use crate::{error::Error};
+use crate::timestamp::{CivilDateTime, Timestamp};
 
 pub struct Camera;
 
 impl Camera {
     /// Prompt the user to pick a photo from an album, or take a new photo with the camera.
+    /// Returns an error if the quality is greater than 100.
     pub async fn get_photo(options: impl Into<ImageOptions>) -> Result<Photo, Error> {
-        run_value_value(options, camera_get_photo).await
+        let options: ImageOptions = options.into();
+        options.validate()?;
+        run_value_value::<_, ImageOptions, _, _>(options, camera_get_photo).await
     }
     /// Allows the user to pick multiple pictures from the photo gallery. On iOS 13 and older it only allows to pick one picture.
+    /// Returns an error if the quality is greater than 100.
     pub async fn pick_images(
         options: impl Into<GalleryImageOptions>,
     ) -> Result<GalleryPhotos, Error> {
-        run_value_value(options, camera_pick_images).await
+        let options: GalleryImageOptions = options.into();
+        options.validate()?;
+        run_value_value::<_, GalleryImageOptions, _, _>(options, camera_pick_images).await
     }
 
     /// Allows the user to pick multiple pictures from the photo gallery. On iOS 13 and older it only allows to pick one picture.
@@ -66,45 +77,122 @@ pub struct Photo {
     pub saved: Option<bool>,
 }
 
+impl Photo {
+    /// The raw bytes of the image.
+    /// These are decoded from `base64_string` or `data_url` if present, and otherwise fetched from `web_path` or `path`.
+    pub async fn bytes(&self) -> Result<Vec<u8>, Error> {
+        if let Some(base64_string) = &self.base64_string {
+            decode_base64(base64_string)
+        } else if let Some(data_url) = &self.data_url {
+            decode_data_url(data_url)
+        } else {
+            fetch_photo(self.web_path.as_deref(), self.path.as_deref()).await
+        }
+    }
+
+    /// The MIME type of the image, taken from the data url or the format.
+    /// Use `detect_mime_type` on the bytes to check the actual content.
+    pub fn mime_type(&self) -> Option<&str> {
+        self.data_url
+            .as_deref()
+            .and_then(data_url_mime_type)
+            .or_else(|| mime_type_from_format(&self.format))
+    }
+}
+
 #[skip_serializing_none]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TypedBuilder)]
+#[serde(rename_all = "camelCase", default)]
 pub struct ImageOptions {
+    #[builder(default = DEFAULT_QUALITY)]
     /// The quality of image to return as JPEG, from 0-100
     pub quality: u8,
+    #[builder(default)]
     /// Whether to allow the user to crop or make small edits (platform specific). On iOS 14+ it's only supported for CameraSource.Camera, but not for CameraSource.Photos.
     pub allow_editing: bool,
+    #[builder(default)]
     /// How the data should be returned. Currently, only 'Base64', 'DataUrl' or 'Uri' is supported
     pub result_type: CameraResultType,
+    #[builder(default)]
     /// Whether to save the photo to the gallery. If the photo was picked from the gallery, it will only be saved if edited.
     pub save_to_gallery: bool,
 
+    #[builder(setter(strip_option), default)]
     /// The desired maximum width of the saved image. The aspect ratio is respected.
-    pub width: u32,
+    pub width: Option<u32>,
+    #[builder(setter(strip_option), default)]
     /// The desired maximum height of the saved image. The aspect ratio is respected.
-    pub height: u32,
+    pub height: Option<u32>,
+    #[builder(default = true)]
     /// Whether to automatically rotate the image "up" to correct for orientation in portrait mode
     pub correct_orientation: bool,
+    #[builder(default)]
     /// The source to get the photo from. By default this prompts the user to select either the photo album or take a photo.
     pub source: CameraSource,
-    /// iOS and Web only: The camera direction.
+    #[builder(setter(strip_option), default)]
+    /// iOS and Web only: The camera direction. Defaults to `Rear`.
     pub direction: Option<CameraDirection>,
 
-    /// iOS only: The presentation style of the Camera.
+    #[builder(setter(strip_option), default)]
+    /// iOS only: The presentation style of the Camera. Defaults to `Fullscreen`.
     pub presentation_style: Option<PresentationStyle>,
+    #[builder(setter(strip_option), default)]
     /// Web only: Whether to use the PWA Element experience or file input. The default is to use PWA Elements if installed and fall back to file input. To always use file input, set this to true. Learn more about PWA Elements: https://capacitorjs.com/docs/web/pwa-elements
     pub web_use_input: Option<bool>,
 
-    /// Text value to use when displaying the prompt.
-    pub prompt_label_header: String,
-    /// Text value to use when displaying the prompt. iOS only: The label of the 'cancel' button.
+    #[builder(setter(into, strip_option), default)]
+    /// Text value to use when displaying the prompt. Defaults to 'Photo'.
+    pub prompt_label_header: Option<String>,
+    #[builder(setter(into, strip_option), default)]
+    /// Text value to use when displaying the prompt. iOS only: The label of the 'cancel' button. Defaults to 'Cancel'.
     pub prompt_label_cancel: Option<String>,
-    /// Text value to use when displaying the prompt. The label of the button to select a saved image.
+    #[builder(setter(into, strip_option), default)]
+    /// Text value to use when displaying the prompt. The label of the button to select a saved image. Defaults to 'From Photos'.
     pub prompt_label_photo: Option<String>,
-    /// Text value to use when displaying the prompt. The label of the button to open the camera.
+    #[builder(setter(into, strip_option), default)]
+    /// Text value to use when displaying the prompt. The label of the button to open the camera. Defaults to 'Take Picture'.
     pub prompt_label_picture: Option<String>,
 }
 
+/// The quality used by Capacitor when none is given.
+pub const DEFAULT_QUALITY: u8 = 90;
+
+/// The highest valid image quality.
+pub const MAX_QUALITY: u8 = 100;
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl ImageOptions {
+    /// Take a new photo with the camera, returned as a JPEG of the given quality.
+    pub fn camera_jpeg(quality: u8) -> Self {
+        Self::builder()
+            .quality(quality)
+            .source(CameraSource::Camera)
+            .build()
+    }
+
+    /// Pick an existing photo from the gallery.
+    pub fn pick_from_gallery() -> Self {
+        Self::builder().source(CameraSource::Photos).build()
+    }
+
+    /// Check that the options are valid.
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_quality(self.quality)
+    }
+}
+
+fn validate_quality(quality: u8) -> Result<(), Error> {
+    if quality > MAX_QUALITY {
+        return Err(Error::InvalidImageQuality { quality });
+    }
+    Ok(())
+}
+
 #[skip_serializing_none]
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
@@ -127,32 +215,319 @@ pub struct GalleryPhoto {
     pub format: String,
 }
 
+impl GalleryPhoto {
+    /// The raw bytes of the image, fetched from `web_path` or `path`.
+    pub async fn bytes(&self) -> Result<Vec<u8>, Error> {
+        fetch_photo(self.web_path.as_deref(), Some(self.path.as_str())).await
+    }
+
+    /// The MIME type of the image, taken from the format.
+    /// Use `detect_mime_type` on the bytes to check the actual content.
+    pub fn mime_type(&self) -> Option<&'static str> {
+        mime_type_from_format(&self.format)
+    }
+}
+
+/// The MIME type for an image format, ex: jpeg, png, gif.
+pub fn mime_type_from_format(format: &str) -> Option<&'static str> {
+    let mime_type = match format.to_ascii_lowercase().as_str() {
+        "jpeg" | "jpg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "heic" => "image/heic",
+        "heif" => "image/heif",
+        "bmp" => "image/bmp",
+        "tiff" | "tif" => "image/tiff",
+        _ => return None,
+    };
+    Some(mime_type)
+}
+
+/// Detect the MIME type of an image from its magic bytes.
+pub fn detect_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    let mime_type = if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "image/jpeg"
+    } else if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        "image/png"
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        "image/gif"
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        "image/webp"
+    } else if bytes.get(4..8) == Some(b"ftyp") {
+        match bytes.get(8..12)? {
+            b"heic" | b"heix" | b"heim" | b"heis" => "image/heic",
+            b"mif1" | b"msf1" | b"heif" => "image/heif",
+            _ => return None,
+        }
+    } else if bytes.starts_with(b"BM") {
+        "image/bmp"
+    } else if bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*") {
+        "image/tiff"
+    } else {
+        return None;
+    };
+    Some(mime_type)
+}
+
+fn decode_base64(data: &str) -> Result<Vec<u8>, Error> {
+    let data: String = data.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    STANDARD.decode(data).map_err(|e| Error::InvalidPhotoData {
+        message: format!("Not valid base64: {e}"),
+    })
+}
+
+/// Split a data url into its header (ex: `data:image/jpeg;base64`) and data.
+fn split_data_url(data_url: &str) -> Option<(&str, &str)> {
+    data_url
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(','))
+}
+
+fn data_url_mime_type(data_url: &str) -> Option<&str> {
+    let (header, _) = split_data_url(data_url)?;
+    let mime_type = header.split(';').next()?;
+    (!mime_type.is_empty()).then_some(mime_type)
+}
+
+fn decode_data_url(data_url: &str) -> Result<Vec<u8>, Error> {
+    let (header, data) = split_data_url(data_url).ok_or_else(|| Error::InvalidPhotoData {
+        message: "Not a data url".to_string(),
+    })?;
+    if !header.ends_with(";base64") {
+        return Err(Error::InvalidPhotoData {
+            message: "Data url is not base64 encoded".to_string(),
+        });
+    }
+    decode_base64(data)
+}
+
+/// Fetch a photo from its web path or, failing that, its platform-specific file path.
+async fn fetch_photo(web_path: Option<&str>, path: Option<&str>) -> Result<Vec<u8>, Error> {
+    let url = match (web_path, path) {
+        (Some(web_path), _) if !web_path.is_empty() => web_path.to_string(),
+        (_, Some(path)) if !path.is_empty() => convert_file_src(path),
+        _ => return Err(Error::PhotoDataMissing),
+    };
+
+    let response: FetchResponse = fetch(&url).await?.unchecked_into();
+    if !response.ok() {
+        return Err(Error::PhotoFetchError {
+            url,
+            status: response.status(),
+        });
+    }
+    let buffer = response.array_buffer().await?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
+
 #[skip_serializing_none]
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
 pub struct ExifData {
+    /// The raw exif data.
+    /// On iOS this contains dictionaries such as `{Exif}`, `{TIFF}` and `{GPS}`; on Android the tags are flat, ex: `GPSLatitude`.
     #[serde(flatten)]
-    pub data: BTreeMap<String, String>,
+    pub data: BTreeMap<String, Value>,
+}
+
+impl ExifData {
+    /// Find a tag, either in an iOS dictionary or by its flat Android name.
+    fn tag(&self, dictionary: &str, key: &str, flat_key: &str) -> Option<&Value> {
+        self.data
+            .get(dictionary)
+            .and_then(|x| x.get(key))
+            .or_else(|| self.data.get(flat_key))
+    }
+
+    fn string_tag(&self, dictionary: &str, key: &str) -> Option<&str> {
+        self.tag(dictionary, key, key)?
+            .as_str()
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+    }
+
+    /// The orientation of the image, from 1 to 8 as defined by the exif standard. 1 means the image is upright.
+    pub fn orientation(&self) -> Option<u8> {
+        let value = self.tag("{TIFF}", "Orientation", "Orientation")?;
+        number(value)
+            .map(|x| x as u8)
+            .filter(|x| (1..=8).contains(x))
+    }
+
+    /// The local date and time at which the photo was taken.
+    pub fn capture_date_time(&self) -> Option<CivilDateTime> {
+        let value = self
+            .string_tag("{Exif}", "DateTimeOriginal")
+            .or_else(|| self.string_tag("{TIFF}", "DateTime"))?;
+        parse_exif_date_time(value)
+    }
+
+    /// The time at which the photo was taken.
+    /// Returns `None` unless the exif data includes the time zone offset.
+    pub fn capture_timestamp(&self) -> Option<Timestamp> {
+        let date_time = self.string_tag("{Exif}", "DateTimeOriginal")?;
+        let offset = self.string_tag("{Exif}", "OffsetTimeOriginal")?;
+        parse_exif_date_time(date_time)?;
+        let (date, time) = date_time.split_once(' ')?;
+        format!("{}T{time}{offset}", date.replace(':', "-"))
+            .parse()
+            .ok()
+    }
+
+    /// The latitude at which the photo was taken, in degrees. Negative values are south.
+    pub fn latitude(&self) -> Option<f64> {
+        let latitude = degrees(self.tag("{GPS}", "Latitude", "GPSLatitude")?)?;
+        let reference = self.tag("{GPS}", "LatitudeRef", "GPSLatitudeRef");
+        Some(match reference.and_then(Value::as_str) {
+            Some("S") => -latitude.abs(),
+            _ => latitude,
+        })
+    }
+
+    /// The longitude at which the photo was taken, in degrees. Negative values are west.
+    pub fn longitude(&self) -> Option<f64> {
+        let longitude = degrees(self.tag("{GPS}", "Longitude", "GPSLongitude")?)?;
+        let reference = self.tag("{GPS}", "LongitudeRef", "GPSLongitudeRef");
+        Some(match reference.and_then(Value::as_str) {
+            Some("W") => -longitude.abs(),
+            _ => longitude,
+        })
+    }
+
+    /// The altitude at which the photo was taken, in metres. Negative values are below sea level.
+    pub fn altitude(&self) -> Option<f64> {
+        let altitude = number(self.tag("{GPS}", "Altitude", "GPSAltitude")?)?;
+        let reference = self.tag("{GPS}", "AltitudeRef", "GPSAltitudeRef");
+        if reference.and_then(number) == Some(1.0) {
+            Some(-altitude.abs())
+        } else {
+            Some(altitude)
+        }
+    }
+
+    /// The manufacturer of the camera.
+    pub fn make(&self) -> Option<&str> {
+        self.string_tag("{TIFF}", "Make")
+    }
+
+    /// The model of the camera.
+    pub fn model(&self) -> Option<&str> {
+        self.string_tag("{TIFF}", "Model")
+    }
+
+    /// The exposure time, in seconds.
+    pub fn exposure_time(&self) -> Option<f64> {
+        number(self.tag("{Exif}", "ExposureTime", "ExposureTime")?)
+    }
+
+    /// The f-number of the lens.
+    pub fn f_number(&self) -> Option<f64> {
+        number(self.tag("{Exif}", "FNumber", "FNumber")?)
+    }
+
+    /// The ISO speed.
+    pub fn iso(&self) -> Option<u32> {
+        let value = self
+            .tag("{Exif}", "ISOSpeedRatings", "ISOSpeedRatings")
+            .or_else(|| self.data.get("PhotographicSensitivity"))?;
+        number(value).map(|x| x as u32)
+    }
+}
+
+/// Read a number which may be a json number, a decimal or rational string (ex: `"1/125"`), or an array whose first element is one of these.
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(x) => x.as_f64(),
+        Value::String(x) => {
+            let x = x.trim();
+            let x = x.split(',').next()?;
+            match x.split_once('/') {
+                Some((numerator, denominator)) => {
+                    let numerator: f64 = numerator.trim().parse().ok()?;
+                    let denominator: f64 = denominator.trim().parse().ok()?;
+                    (denominator != 0.0).then(|| numerator / denominator)
+                }
+                None => x.parse().ok(),
+            }
+        }
+        Value::Array(x) => number(x.first()?),
+        _ => None,
+    }
+}
+
+/// Read an angle which may be in decimal degrees or, as on Android, degrees, minutes and seconds (ex: `"51/1,30/1,1234/100"`).
+fn degrees(value: &Value) -> Option<f64> {
+    let parts: Vec<f64> = match value {
+        Value::String(x) => x
+            .split(',')
+            .map(|part| number(&Value::String(part.to_string())))
+            .collect::<Option<_>>()?,
+        Value::Array(x) => x.iter().map(number).collect::<Option<_>>()?,
+        _ => vec![number(value)?],
+    };
+    let mut parts = parts.into_iter();
+    let degrees = parts.next()?;
+    let minutes = parts.next().unwrap_or_default();
+    let seconds = parts.next().unwrap_or_default();
+    Some(degrees + minutes / 60.0 + seconds / 3600.0)
+}
+
+/// Parse an exif date and time, ex: `2024:03:01 09:30:00`
+fn parse_exif_date_time(value: &str) -> Option<CivilDateTime> {
+    let (date, time) = value.trim().split_once(' ')?;
+    let mut date = date.split(':').map(str::parse::<u32>);
+    let mut time = time.split(':').map(str::parse::<u32>);
+    let civil = CivilDateTime {
+        year: date.next()?.ok()? as i32,
+        month: date.next()?.ok()?,
+        day: date.next()?.ok()?,
+        hour: time.next()?.ok()?,
+        minute: time.next()?.ok()?,
+        second: time.next()?.ok()?,
+        millisecond: 0,
+    };
+    // Unknown dates are written as `0000:00:00 00:00:00`
+    Timestamp::from_civil(civil).map(|_| civil)
 }
 
 #[skip_serializing_none]
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TypedBuilder)]
 #[serde(rename_all = "camelCase", default)]
 pub struct GalleryImageOptions {
+    #[builder(default = DEFAULT_QUALITY)]
     /// The quality of image to return as JPEG, from 0-100
     pub quality: u8,
+    #[builder(setter(strip_option), default)]
     /// The desired maximum width of the saved image. The aspect ratio is respected.
-    pub width: u32,
+    pub width: Option<u32>,
+    #[builder(setter(strip_option), default)]
     /// The desired maximum height of the saved image. The aspect ratio is respected.
-    pub height: u32,
+    pub height: Option<u32>,
+    #[builder(default = true)]
     /// Whether to automatically rotate the image "up" to correct for orientation in portrait mode
     pub correct_orientation: bool,
+    #[builder(setter(strip_option), default)]
     /// iOS only: The presentation style of the Camera.
     pub presentation_style: Option<PresentationStyle>,
-    /// iOS only: Maximum number of pictures the user will be able to choose.
+    #[builder(setter(strip_option), default)]
+    /// iOS only: Maximum number of pictures the user will be able to choose. Defaults to 0, which means unlimited.
     pub limit: Option<u32>,
 }
 
+impl Default for GalleryImageOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl GalleryImageOptions {
+    /// Check that the options are valid.
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_quality(self.quality)
+    }
+}
+
 #[skip_serializing_none]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -212,17 +587,19 @@ pub enum CameraPermissionType {
     Photos,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum CameraResultType {
+    #[default]
     Uri,
     Base64,
     DataUrl,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum CameraSource {
+    #[default]
     /// Prompts the user to select either the photo album or take a photo.
     Prompt,
     /// Take a new photo using the camera.
@@ -237,3 +614,142 @@ pub enum CameraDirection {
     Rear,
     Front,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_data_url() {
+        let data_url = "data:image/png;base64,iVBORw0KGgo=";
+        let bytes = decode_data_url(data_url).unwrap();
+        assert_eq!(bytes, [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+        assert_eq!(detect_mime_type(&bytes), Some("image/png"));
+        assert_eq!(data_url_mime_type(data_url), Some("image/png"));
+
+        assert_eq!(
+            decode_base64("/9j/\n4A==").unwrap(),
+            [0xFF, 0xD8, 0xFF, 0xE0]
+        );
+        assert!(decode_data_url("data:text/plain,hello").is_err());
+        assert!(decode_data_url("iVBORw0KGgo=").is_err());
+    }
+
+    #[test]
+    fn test_mime_type() {
+        let photo = Photo {
+            format: "JPEG".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(photo.mime_type(), Some("image/jpeg"));
+        assert_eq!(mime_type_from_format("bin"), None);
+
+        assert_eq!(detect_mime_type(b"GIF89a..."), Some("image/gif"));
+        assert_eq!(
+            detect_mime_type(b"RIFF\0\0\0\0WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(detect_mime_type(b"\0\0\0\x18ftypheic"), Some("image/heic"));
+        assert_eq!(detect_mime_type(&[0xFF, 0xD8]), None);
+    }
+
+    #[test]
+    fn test_exif_ios() {
+        let exif: ExifData = serde_json::from_str(
+            r#"{
+                "Orientation": 6,
+                "{Exif}": {
+                    "DateTimeOriginal": "2024:03:01 09:30:15",
+                    "OffsetTimeOriginal": "+01:00",
+                    "ExposureTime": 0.008,
+                    "FNumber": 1.8,
+                    "ISOSpeedRatings": [100]
+                },
+                "{TIFF}": { "Make": "Apple", "Model": "iPhone 15" },
+                "{GPS}": {
+                    "Latitude": 51.5,
+                    "LatitudeRef": "N",
+                    "Longitude": 0.125,
+                    "LongitudeRef": "W",
+                    "Altitude": 12.5,
+                    "AltitudeRef": 0
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(exif.orientation(), Some(6));
+        let civil = exif.capture_date_time().unwrap();
+        assert_eq!((civil.year, civil.month, civil.day), (2024, 3, 1));
+        assert_eq!((civil.hour, civil.minute, civil.second), (9, 30, 15));
+        assert_eq!(
+            exif.capture_timestamp().unwrap().to_string(),
+            "2024-03-01T08:30:15.000Z"
+        );
+        assert_eq!(exif.exposure_time(), Some(0.008));
+        assert_eq!(exif.f_number(), Some(1.8));
+        assert_eq!(exif.iso(), Some(100));
+        assert_eq!(exif.make(), Some("Apple"));
+        assert_eq!(exif.model(), Some("iPhone 15"));
+        assert_eq!(exif.latitude(), Some(51.5));
+        assert_eq!(exif.longitude(), Some(-0.125));
+        assert_eq!(exif.altitude(), Some(12.5));
+    }
+
+    #[test]
+    fn test_exif_android() {
+        let exif: ExifData = serde_json::from_str(
+            r#"{
+                "Orientation": "1",
+                "DateTimeOriginal": "2024:03:01 09:30:15",
+                "ExposureTime": "1/125",
+                "FNumber": "1.8",
+                "PhotographicSensitivity": "200",
+                "Make": "Google",
+                "Model": "Pixel 8",
+                "GPSLatitude": "33/1,52/1,1800/100",
+                "GPSLatitudeRef": "S",
+                "GPSLongitude": "151/1,12/1,3600/100",
+                "GPSLongitudeRef": "E",
+                "GPSAltitude": "5/2",
+                "GPSAltitudeRef": "1"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(exif.orientation(), Some(1));
+        assert_eq!(exif.capture_date_time().unwrap().hour, 9);
+        assert_eq!(exif.capture_timestamp(), None);
+        assert_eq!(exif.exposure_time(), Some(0.008));
+        assert_eq!(exif.f_number(), Some(1.8));
+        assert_eq!(exif.iso(), Some(200));
+        assert_eq!(exif.make(), Some("Google"));
+        assert_eq!(exif.model(), Some("Pixel 8"));
+        assert_eq!(exif.latitude(), Some(-(33.0 + 52.0 / 60.0 + 18.0 / 3600.0)));
+        assert_eq!(exif.longitude(), Some(151.0 + 12.0 / 60.0 + 36.0 / 3600.0));
+        assert_eq!(exif.altitude(), Some(-2.5));
+
+        let unknown: ExifData =
+            serde_json::from_str(r#"{"DateTimeOriginal": "0000:00:00 00:00:00"}"#).unwrap();
+        assert_eq!(unknown.capture_date_time(), None);
+    }
+
+    #[test]
+    fn test_image_options() {
+        let options = ImageOptions::builder().width(400).build();
+        assert_eq!(
+            serde_json::to_string(&options).unwrap(),
+            r#"{"quality":90,"allowEditing":false,"resultType":"uri","saveToGallery":false,"width":400,"correctOrientation":true,"source":"PROMPT"}"#
+        );
+        assert!(options.validate().is_ok());
+        assert!(ImageOptions::camera_jpeg(101).validate().is_err());
+        assert_eq!(
+            ImageOptions::pick_from_gallery().source,
+            CameraSource::Photos
+        );
+
+        let gallery: GalleryImageOptions = serde_json::from_str(r#"{"limit":3}"#).unwrap();
+        assert_eq!(gallery.quality, DEFAULT_QUALITY);
+        assert!(gallery.correct_orientation);
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::camera::{Camera, CameraPermissionState, PermissionStatus};
+use crate::device::{Device, DeviceId, DeviceInfo, Platform};
+use crate::error::Error;
+use crate::local_notifications::LocalNotifications;
+use crate::network::{ConnectionStatus, ConnectionType, Network};
+use crate::share::Share;
+
+/// A snapshot of the device's capabilities, gathered from several plugins at once.
+/// Useful for logging, attaching to bug reports and deciding which features to show.
+#[skip_serializing_none]
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeviceProfile {
+    /// Information about the device, from `Device::get_info`.
+    pub info: Option<DeviceInfo>,
+    /// The device identifier, from `Device::get_id`.
+    pub id: Option<DeviceId>,
+    /// The network status, from `Network::get_status`.
+    pub network: Option<ConnectionStatus>,
+    /// Whether a screen reader is active, from `ScreenReader::is_enabled`.
+    /// Always `None` when built without the `ios` or `android` feature. When built with one of them but running on web, this is `None` and the plugin's error is recorded in `errors`.
+    pub screen_reader_enabled: Option<bool>,
+    /// Whether sharing is supported, from `Share::can_share`.
+    pub can_share: Option<bool>,
+    /// Whether local notifications are enabled, from `LocalNotifications::are_enabled`.
+    pub notifications_enabled: Option<bool>,
+    /// The camera and photo permissions, from `Camera::check_permissions`.
+    pub camera_permissions: Option<PermissionStatus>,
+    /// The error messages of any probes that failed, keyed by the name of the field they would have filled.
+    pub errors: BTreeMap<String, String>,
+}
+
+impl DeviceProfile {
+    /// Run all of the probes concurrently and collect the results.
+    /// Probes which fail leave their field empty and record an error in `errors`.
+    pub async fn collect() -> Self {
+        #[cfg(any(feature = "ios", feature = "android"))]
+        let screen_reader = async {
+            crate::screen_reader::ScreenReader::is_enabled()
+                .await
+                .map(|x| Some(x.value))
+        };
+        #[cfg(not(any(feature = "ios", feature = "android")))]
+        let screen_reader = async { Result::<Option<bool>, Error>::Ok(None) };
+
+        let (info, id, network, screen_reader, can_share, notifications, camera) = futures::join!(
+            Device::get_info(),
+            Device::get_id(),
+            Network::get_status(),
+            screen_reader,
+            Share::can_share(),
+            LocalNotifications::are_enabled(),
+            Camera::check_permissions(),
+        );
+
+        let mut profile = Self::default();
+        profile.info = profile.record("info", info);
+        profile.id = profile.record("id", id);
+        profile.network = profile.record("network", network);
+        profile.screen_reader_enabled = profile
+            .record("screenReaderEnabled", screen_reader)
+            .flatten();
+        profile.can_share = profile.record("canShare", can_share).map(|x| x.value);
+        profile.notifications_enabled = profile
+            .record("notificationsEnabled", notifications)
+            .map(|x| x.value);
+        profile.camera_permissions = profile.record("cameraPermissions", camera);
+        profile
+    }
+
+    fn record<T>(&mut self, probe: &str, result: Result<T, Error>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.errors.insert(probe.to_string(), error.to_string());
+                None
+            }
+        }
+    }
+
+    /// The device platform, if known.
+    pub fn platform(&self) -> Option<Platform> {
+        self.info.as_ref().map(|x| x.platform)
+    }
+
+    /// Whether the app is running in a simulator or emulator.
+    pub fn is_virtual(&self) -> bool {
+        self.info.as_ref().is_some_and(|x| x.is_virtual)
+    }
+
+    /// Whether the device has an active network connection.
+    pub fn is_connected(&self) -> bool {
+        self.network.as_ref().is_some_and(|x| x.connected)
+    }
+
+    /// Whether the device is connected via wifi.
+    pub fn is_on_wifi(&self) -> bool {
+        self.network
+            .as_ref()
+            .is_some_and(|x| x.connected && x.connection_type == ConnectionType::Wifi)
+    }
+
+    /// Whether a screen reader is known to be active.
+    pub fn is_screen_reader_enabled(&self) -> bool {
+        self.screen_reader_enabled.unwrap_or_default()
+    }
+
+    /// Whether sharing is known to be supported.
+    pub fn can_share(&self) -> bool {
+        self.can_share.unwrap_or_default()
+    }
+
+    /// Whether local notifications are known to be enabled.
+    pub fn are_notifications_enabled(&self) -> bool {
+        self.notifications_enabled.unwrap_or_default()
+    }
+
+    /// Whether the camera can be used, possibly after prompting the user for permission.
+    pub fn can_use_camera(&self) -> bool {
+        self.camera_permissions
+            .as_ref()
+            .is_some_and(|x| x.camera != CameraPermissionState::Denied)
+    }
+}
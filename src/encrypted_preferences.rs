@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::Error;
+use crate::preferences::{from_json, to_json, GetOptions, Preferences, SetOptions};
+
+const PREFIX: &str = "enc:v";
+const NONCE_LENGTH: usize = 12;
+
+/// The number of PBKDF2-HMAC-SHA256 rounds recommended by OWASP for deriving a key from a passphrase.
+pub const RECOMMENDED_PBKDF2_ROUNDS: u32 = 600_000;
+
+/// A 256 bit key used to encrypt preference values.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey(Key);
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+impl EncryptionKey {
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes.into())
+    }
+
+    /// Generate a new random key.
+    pub fn generate() -> Self {
+        Self(ChaCha20Poly1305::generate_key(&mut OsRng))
+    }
+
+    /// Derive a key from a passphrase using PBKDF2-HMAC-SHA256.
+    /// The salt should be random, at least 16 bytes long, and stored alongside the encrypted values.
+    pub fn from_passphrase(passphrase: &str, salt: &[u8], rounds: u32) -> Self {
+        let mut bytes = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<sha2::Sha256>(passphrase.as_bytes(), salt, rounds, &mut bytes);
+        Self::from_bytes(bytes)
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.into()
+    }
+}
+
+/// Stores values in preferences encrypted with ChaCha20-Poly1305.
+///
+/// Each value is tagged with the version of the key that encrypted it, so keys can be rotated:
+/// values are always encrypted with the current key, but can be decrypted with any known key.
+/// The preference key is used as associated data, so an encrypted value cannot be moved to a different key.
+#[derive(Clone, Debug)]
+pub struct EncryptedPreferences {
+    current_version: u32,
+    keys: BTreeMap<u32, EncryptionKey>,
+}
+
+impl EncryptedPreferences {
+    /// Create with the key used to encrypt new values and its version.
+    pub fn new(version: u32, key: EncryptionKey) -> Self {
+        Self {
+            current_version: version,
+            keys: BTreeMap::from([(version, key)]),
+        }
+    }
+
+    /// Add an older key, which will only be used to decrypt values.
+    pub fn with_previous_key(mut self, version: u32, key: EncryptionKey) -> Self {
+        self.keys.entry(version).or_insert(key);
+        self
+    }
+
+    /// The version of the key used to encrypt new values.
+    pub fn current_version(&self) -> u32 {
+        self.current_version
+    }
+
+    /// Encrypt a value to be stored under the given preference key.
+    pub fn encrypt(&self, key: &str, value: &str) -> Result<String, Error> {
+        let cipher = ChaCha20Poly1305::new(&self.keys[&self.current_version].0);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: value.as_bytes(),
+            aad: key.as_bytes(),
+        };
+        let ciphertext = cipher
+            .encrypt(&nonce, payload)
+            .map_err(|_| encryption_error(key, "Could not encrypt value".to_string()))?;
+
+        let mut bytes = nonce.to_vec();
+        bytes.extend(ciphertext);
+        Ok(format!(
+            "{PREFIX}{}:{}",
+            self.current_version,
+            STANDARD.encode(bytes)
+        ))
+    }
+
+    /// Decrypt a value which was stored under the given preference key.
+    pub fn decrypt(&self, key: &str, stored: &str) -> Result<String, Error> {
+        let (version, data) = parse_stored(stored)
+            .ok_or_else(|| encryption_error(key, "Value is not encrypted".to_string()))?;
+        let encryption_key = self.keys.get(&version).ok_or_else(|| {
+            encryption_error(
+                key,
+                format!("Value was encrypted with unknown key version {version}"),
+            )
+        })?;
+        let bytes = STANDARD
+            .decode(data)
+            .map_err(|e| encryption_error(key, format!("Value is not valid base64: {e}")))?;
+        if bytes.len() < NONCE_LENGTH {
+            return Err(encryption_error(key, "Value is too short".to_string()));
+        }
+
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LENGTH);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: key.as_bytes(),
+        };
+        let plaintext = ChaCha20Poly1305::new(&encryption_key.0)
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| {
+                encryption_error(
+                    key,
+                    "Value could not be authenticated. It may have been encrypted with a different key or modified".to_string(),
+                )
+            })?;
+
+        String::from_utf8(plaintext)
+            .map_err(|_| encryption_error(key, "Decrypted value is not valid utf-8".to_string()))
+    }
+
+    /// Encrypt a value and set it in preferences for a given key.
+    pub async fn set(&self, key: &str, value: &str) -> Result<(), Error> {
+        let value = self.encrypt(key, value)?;
+        Preferences::set(SetOptions {
+            key: key.to_string(),
+            value,
+        })
+        .await
+    }
+
+    /// Get the value from preferences of a given key and decrypt it.
+    /// Returns `Error::EncryptionError` if the value cannot be decrypted.
+    pub async fn get(&self, key: &str) -> Result<Option<String>, Error> {
+        let result = Preferences::get(GetOptions::from(key)).await?;
+        result
+            .value
+            .map(|value| self.decrypt(key, &value))
+            .transpose()
+    }
+
+    /// Serialize a value as json, encrypt it and set it in preferences for a given key.
+    pub async fn set_json<T: Serialize>(&self, key: &str, value: &T) -> Result<(), Error> {
+        self.set(key, &to_json(value)?).await
+    }
+
+    /// Get the value from preferences of a given key, decrypt it and deserialize it from json.
+    pub async fn get_json<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, Error> {
+        self.get(key)
+            .await?
+            .map(|value| from_json(key, &value))
+            .transpose()
+    }
+
+    /// Re-encrypt the value of a given key with the current key, if it was encrypted with an older one.
+    /// Returns whether the value was changed.
+    pub async fn rotate(&self, key: &str) -> Result<bool, Error> {
+        let Some(stored) = Preferences::get(GetOptions::from(key)).await?.value else {
+            return Ok(false);
+        };
+        if parse_stored(&stored).is_some_and(|(version, _)| version == self.current_version) {
+            return Ok(false);
+        }
+
+        let value = self.decrypt(key, &stored)?;
+        self.set(key, &value).await?;
+        Ok(true)
+    }
+}
+
+fn parse_stored(stored: &str) -> Option<(u32, &str)> {
+    let (version, data) = stored.strip_prefix(PREFIX)?.split_once(':')?;
+    Some((version.parse().ok()?, data))
+}
+
+fn encryption_error(key: &str, message: String) -> Error {
+    Error::EncryptionError {
+        key: key.to_string(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let preferences = EncryptedPreferences::new(1, EncryptionKey::generate());
+
+        let encrypted = preferences.encrypt("token", "secret value").unwrap();
+        assert!(encrypted.starts_with("enc:v1:"));
+        assert!(!encrypted.contains("secret"));
+        assert_eq!(
+            preferences.decrypt("token", &encrypted).unwrap(),
+            "secret value"
+        );
+
+        // The value is bound to its key
+        assert!(preferences.decrypt("other", &encrypted).is_err());
+        // Tampered values are rejected
+        let tampered = encrypted.replace("enc:v1:", "enc:v1:AA");
+        assert!(preferences.decrypt("token", &tampered).is_err());
+        assert!(preferences.decrypt("token", "secret value").is_err());
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let old_key = EncryptionKey::from_passphrase("old passphrase", b"0123456789abcdef", 1000);
+        let new_key = EncryptionKey::from_passphrase("new passphrase", b"0123456789abcdef", 1000);
+
+        let old = EncryptedPreferences::new(1, old_key.clone());
+        let new = EncryptedPreferences::new(2, new_key).with_previous_key(1, old_key);
+
+        let encrypted = old.encrypt("token", "secret value").unwrap();
+        assert_eq!(new.decrypt("token", &encrypted).unwrap(), "secret value");
+        assert!(new
+            .encrypt("token", "secret value")
+            .unwrap()
+            .starts_with("enc:v2:"));
+
+        let error = EncryptedPreferences::new(2, EncryptionKey::generate())
+            .decrypt("token", &encrypted)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Encryption Error for preference 'token': Value was encrypted with unknown key version 1"
+        );
+    }
+}
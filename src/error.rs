@@ -20,6 +20,56 @@ pub enum Error {
         typename: &'static str,
         error: serde_wasm_bindgen::Error,
     },
+    JsonSerializeError {
+        typename: &'static str,
+        error: serde_json::Error,
+    },
+    JsonDeserializeError {
+        typename: &'static str,
+        error: serde_json::Error,
+    },
+    /// A value stored in preferences could not be deserialized
+    CorruptPreference {
+        key: String,
+        typename: &'static str,
+        error: serde_json::Error,
+    },
+    /// A typed preference key is newer than the stored schema version, so migrations must be run first
+    PreferenceSchemaOutdated {
+        key: String,
+        version: u32,
+        schema_version: u32,
+    },
+    /// A preferences cache was flushed while a different preferences group was configured
+    PreferencesGroupMismatch {
+        group: String,
+        configured: String,
+    },
+    /// An encrypted value could not be encrypted or decrypted
+    EncryptionError {
+        key: String,
+        message: String,
+    },
+    /// Every notification id in the scheduler's range is in use
+    NotificationIdsExhausted {
+        start: i32,
+        end: i32,
+    },
+    /// A photo has no data or path which its bytes could be read from
+    PhotoDataMissing,
+    /// The base64 or data url of a photo could not be decoded
+    InvalidPhotoData {
+        message: String,
+    },
+    /// A photo could not be fetched from its web path
+    PhotoFetchError {
+        url: String,
+        status: u16,
+    },
+    /// An image quality was greater than 100
+    InvalidImageQuality {
+        quality: u8,
+    },
 }
 
 impl std::fmt::Display for Error {
@@ -33,6 +83,52 @@ impl std::fmt::Display for Error {
                 write!(f, "Error Deserializing JsValue to {typename}: {error}")
             }
             Error::NotAFunction { name } => write!(f, "Not a function: {name}"),
+            Error::JsonSerializeError { typename, error } => {
+                write!(f, "Error Serializing {typename} to Json: {error}")
+            }
+            Error::JsonDeserializeError { typename, error } => {
+                write!(f, "Error Deserializing Json to {typename}: {error}")
+            }
+            Error::CorruptPreference {
+                key,
+                typename,
+                error,
+            } => {
+                write!(
+                    f,
+                    "Error Deserializing preference '{key}' to {typename}: {error}"
+                )
+            }
+            Error::PreferenceSchemaOutdated {
+                key,
+                version,
+                schema_version,
+            } => {
+                write!(
+                    f,
+                    "Preference '{key}' requires schema version {version} but the stored schema version is {schema_version}"
+                )
+            }
+            Error::PreferencesGroupMismatch { group, configured } => {
+                write!(
+                    f,
+                    "Preferences cache belongs to group '{group}' but group '{configured}' is configured"
+                )
+            }
+            Error::EncryptionError { key, message } => {
+                write!(f, "Encryption Error for preference '{key}': {message}")
+            }
+            Error::NotificationIdsExhausted { start, end } => {
+                write!(f, "All notification ids from {start} to {end} are in use")
+            }
+            Error::PhotoDataMissing => write!(f, "Photo has no data or path"),
+            Error::InvalidPhotoData { message } => write!(f, "Invalid photo data: {message}"),
+            Error::PhotoFetchError { url, status } => {
+                write!(f, "Error fetching photo from '{url}': status {status}")
+            }
+            Error::InvalidImageQuality { quality } => {
+                write!(f, "Image quality must be from 0 to 100 but was {quality}")
+            }
         }
     }
 }
@@ -44,6 +140,17 @@ impl std::error::Error for Error {
             Error::NotAFunction { .. } => None,
             Error::SerializeError { error, .. } => error.source(),
             Error::DeserializeError { error, .. } => error.source(),
+            Error::JsonSerializeError { error, .. } => Some(error),
+            Error::JsonDeserializeError { error, .. } => Some(error),
+            Error::CorruptPreference { error, .. } => Some(error),
+            Error::PreferenceSchemaOutdated { .. } => None,
+            Error::PreferencesGroupMismatch { .. } => None,
+            Error::EncryptionError { .. } => None,
+            Error::NotificationIdsExhausted { .. } => None,
+            Error::PhotoDataMissing => None,
+            Error::InvalidPhotoData { .. } => None,
+            Error::PhotoFetchError { .. } => None,
+            Error::InvalidImageQuality { .. } => None,
         }
     }
 }
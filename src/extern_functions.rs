@@ -1,5 +1,50 @@
 use wasm_bindgen::prelude::*;
 
+#[wasm_bindgen()]
+extern "C" {
+    #[wasm_bindgen(js_name = "setTimeout")]
+    pub(crate) fn set_timeout(handler: &js_sys::Function, timeout: i32) -> JsValue;
+
+    #[wasm_bindgen(js_name = "matchMedia")]
+    pub(crate) fn match_media(query: &str) -> MediaQueryList;
+
+    pub(crate) type MediaQueryList;
+
+    #[wasm_bindgen(method, getter)]
+    pub(crate) fn matches(this: &MediaQueryList) -> bool;
+
+    #[wasm_bindgen(method, js_name = "addEventListener")]
+    pub(crate) fn add_event_listener(
+        this: &MediaQueryList,
+        event_name: &str,
+        listener_func: &Closure<dyn Fn(JsValue)>,
+    );
+
+    #[wasm_bindgen(method, js_name = "removeEventListener")]
+    pub(crate) fn remove_event_listener(
+        this: &MediaQueryList,
+        event_name: &str,
+        listener_func: &Closure<dyn Fn(JsValue)>,
+    );
+
+    #[wasm_bindgen(catch, js_name = "fetch")]
+    pub(crate) async fn fetch(url: &str) -> Result<JsValue, JsValue>;
+
+    pub(crate) type FetchResponse;
+
+    #[wasm_bindgen(method, getter)]
+    pub(crate) fn ok(this: &FetchResponse) -> bool;
+
+    #[wasm_bindgen(method, getter)]
+    pub(crate) fn status(this: &FetchResponse) -> u16;
+
+    #[wasm_bindgen(catch, method, js_name = "arrayBuffer")]
+    pub(crate) async fn array_buffer(this: &FetchResponse) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["Capacitor"], js_name = "convertFileSrc")]
+    pub(crate) fn convert_file_src(file_path: &str) -> String;
+}
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(catch, final, js_namespace = ["Capacitor", "Plugins","SplashScreen"], js_name="show" )]
@@ -166,6 +211,24 @@ extern "C" {
         options: JsValue,
     ) -> Result<(), JsValue>;
 
+    #[wasm_bindgen(catch, final,js_namespace = ["Capacitor", "Plugins", "LocalNotifications"], js_name="getPending" )]
+    pub(crate) async fn local_notifications_get_pending() -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, final,js_namespace = ["Capacitor", "Plugins", "LocalNotifications"], js_name="checkExactNotificationSetting" )]
+    pub(crate) async fn local_notifications_check_exact_notification_setting() -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, final,js_namespace = ["Capacitor", "Plugins", "LocalNotifications"], js_name="changeExactNotificationSetting" )]
+    pub(crate) async fn local_notifications_change_exact_notification_setting() -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, final,js_namespace = ["Capacitor", "Plugins", "LocalNotifications"], js_name="createChannel" )]
+    pub(crate) async fn local_notifications_create_channel(channel: JsValue) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, final,js_namespace = ["Capacitor", "Plugins", "LocalNotifications"], js_name="deleteChannel" )]
+    pub(crate) async fn local_notifications_delete_channel(args: JsValue) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, final,js_namespace = ["Capacitor", "Plugins", "LocalNotifications"], js_name="listChannels" )]
+    pub(crate) async fn local_notifications_list_channels() -> Result<JsValue, JsValue>;
+
     #[wasm_bindgen( final,js_namespace = ["Capacitor", "Plugins", "LocalNotifications"], js_name="addListener" )]
     pub(crate) fn local_notifications_add_listener(
         eventName: &str,
@@ -198,10 +261,13 @@ extern "C" {
 #[wasm_bindgen()]
 extern "C" {
     #[wasm_bindgen(catch, final, js_namespace = ["Capacitor", "Plugins", "StatusBar"], js_name="show" )]
-    pub(crate) async fn status_bar_show() -> Result<(), JsValue>;
+    pub(crate) async fn status_bar_show(options: JsValue) -> Result<(), JsValue>;
 
     #[wasm_bindgen(catch, final,js_namespace = ["Capacitor", "Plugins", "StatusBar"], js_name="hide" )]
-    pub(crate) async fn status_bar_hide() -> Result<(), JsValue>;
+    pub(crate) async fn status_bar_hide(options: JsValue) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, final,js_namespace = ["Capacitor", "Plugins", "StatusBar"], js_name="getInfo" )]
+    pub(crate) async fn status_bar_get_info() -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(catch, final,js_namespace = ["Capacitor", "Plugins", "StatusBar"], js_name="setStyle" )]
     pub(crate) async fn status_bar_set_style(options: JsValue) -> Result<(), JsValue>;
@@ -213,6 +279,12 @@ extern "C" {
     /// This method is only supported on Android.
     #[wasm_bindgen(catch, final,js_namespace = ["Capacitor", "Plugins", "StatusBar"], js_name="setOverlaysWebView" )]
     pub(crate) async fn status_bar_set_overlays_web_view(options: JsValue) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(final,js_namespace = ["Capacitor", "Plugins", "StatusBar"], js_name="addListener" )]
+    pub(crate) fn status_bar_add_listener(
+        eventName: &str,
+        listener_func: &Closure<dyn Fn(JsValue)>,
+    ) -> JsValue;
 }
 
 #[wasm_bindgen()]
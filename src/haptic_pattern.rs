@@ -0,0 +1,189 @@
+use std::{cell::Cell, time::Duration};
+
+use crate::error::Error;
+use crate::haptics::{Haptics, ImpactStyle, NotificationType};
+use crate::helpers::sleep;
+
+thread_local! {
+    /// Incremented whenever a pattern starts or is stopped, so that the playing pattern knows to stop.
+    static GENERATION: Cell<u64> = const { Cell::new(0) };
+}
+
+/// A single step in a haptic pattern.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HapticStep {
+    /// Trigger a haptics "impact" feedback.
+    Impact(ImpactStyle),
+    /// Trigger a haptics "notification" feedback.
+    Notification(NotificationType),
+    /// Vibrate the device for this long.
+    Vibrate(Duration),
+    /// Trigger a haptics "selection changed" feedback.
+    Selection,
+    /// Wait for this long.
+    Pause(Duration),
+}
+
+impl HapticStep {
+    /// The vibration used instead of this step if it fails, e.g. on platforms without impact styles.
+    pub fn fallback_duration(&self) -> Option<Duration> {
+        let millis = match self {
+            HapticStep::Impact(ImpactStyle::Heavy) => 60,
+            HapticStep::Impact(ImpactStyle::Medium) => 40,
+            HapticStep::Impact(ImpactStyle::Light) => 20,
+            HapticStep::Notification(NotificationType::Success) => 50,
+            HapticStep::Notification(NotificationType::Warning) => 80,
+            HapticStep::Notification(NotificationType::Error) => 120,
+            HapticStep::Selection => 10,
+            HapticStep::Vibrate(_) | HapticStep::Pause(_) => return None,
+        };
+        Some(Duration::from_millis(millis))
+    }
+
+    /// How long this step takes when played.
+    /// Feedback other than vibration is treated as instantaneous.
+    pub fn duration(&self) -> Duration {
+        match self {
+            HapticStep::Vibrate(duration) | HapticStep::Pause(duration) => *duration,
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// A sequence of haptic feedback to play with one call.
+///
+/// Only one pattern plays at a time: starting a pattern stops any pattern which is already playing.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct HapticPattern {
+    pub steps: Vec<HapticStep>,
+}
+
+impl From<Vec<HapticStep>> for HapticPattern {
+    fn from(steps: Vec<HapticStep>) -> Self {
+        Self { steps }
+    }
+}
+
+impl HapticPattern {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an impact step.
+    pub fn impact(mut self, style: ImpactStyle) -> Self {
+        self.steps.push(HapticStep::Impact(style));
+        self
+    }
+
+    /// Add a notification step.
+    pub fn notification(mut self, notification_type: NotificationType) -> Self {
+        self.steps.push(HapticStep::Notification(notification_type));
+        self
+    }
+
+    /// Add a vibration step.
+    pub fn vibrate(mut self, duration: Duration) -> Self {
+        self.steps.push(HapticStep::Vibrate(duration));
+        self
+    }
+
+    /// Add a selection changed step.
+    pub fn selection(mut self) -> Self {
+        self.steps.push(HapticStep::Selection);
+        self
+    }
+
+    /// Add a pause.
+    pub fn pause(mut self, duration: Duration) -> Self {
+        self.steps.push(HapticStep::Pause(duration));
+        self
+    }
+
+    /// The total time taken to play this pattern.
+    pub fn duration(&self) -> Duration {
+        self.steps.iter().map(HapticStep::duration).sum()
+    }
+
+    /// Play this pattern, stopping any pattern which is already playing.
+    /// If an impact, notification or selection step fails, it and all later such steps are played as vibrations instead.
+    /// Returns `false` if the pattern was stopped before it finished.
+    pub async fn play(&self) -> Result<bool, Error> {
+        let generation = GENERATION.with(|g| {
+            g.set(g.get() + 1);
+            g.get()
+        });
+        let is_current = || GENERATION.with(|g| g.get()) == generation;
+
+        let mut use_vibrate = false;
+
+        for step in self.steps.iter() {
+            if !is_current() {
+                return Ok(false);
+            }
+
+            if use_vibrate {
+                if let Some(duration) = step.fallback_duration() {
+                    Self::vibrate_for(duration).await?;
+                    continue;
+                }
+            }
+
+            let result = match step {
+                HapticStep::Impact(style) => Haptics::impact(*style).await,
+                HapticStep::Notification(notification_type) => {
+                    Haptics::notification(*notification_type).await
+                }
+                HapticStep::Selection => Haptics::selection_changed().await,
+                HapticStep::Vibrate(duration) => Self::vibrate_for(*duration).await,
+                HapticStep::Pause(duration) => {
+                    sleep(*duration).await;
+                    Ok(())
+                }
+            };
+
+            if let Err(error) = result {
+                let Some(duration) = step.fallback_duration() else {
+                    return Err(error);
+                };
+                use_vibrate = true;
+                Self::vibrate_for(duration).await?;
+            }
+        }
+
+        Ok(is_current())
+    }
+
+    /// Stop the pattern which is currently playing, if any.
+    pub fn stop() {
+        GENERATION.with(|g| g.set(g.get() + 1));
+    }
+
+    /// Vibrate and wait for the vibration to finish.
+    async fn vibrate_for(duration: Duration) -> Result<(), Error> {
+        Haptics::vibrate(duration.as_secs_f64() * 1000.0).await?;
+        sleep(duration).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration() {
+        let pattern = HapticPattern::new()
+            .impact(ImpactStyle::Heavy)
+            .pause(Duration::from_millis(100))
+            .vibrate(Duration::from_millis(250))
+            .notification(NotificationType::Success);
+
+        assert_eq!(pattern.steps.len(), 4);
+        assert_eq!(pattern.duration(), Duration::from_millis(350));
+        assert_eq!(
+            pattern.steps[0].fallback_duration(),
+            Some(Duration::from_millis(60))
+        );
+        assert_eq!(pattern.steps[2].fallback_duration(), None);
+    }
+}
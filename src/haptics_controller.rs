@@ -0,0 +1,257 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::haptic_pattern::{HapticPattern, HapticStep};
+use crate::haptics::{Haptics, ImpactOptions, ImpactStyle, NotificationOptions, VibrateOptions};
+use crate::preference_keys::PrefKey;
+use crate::preferences::Preferences;
+
+/// Whether the user has enabled haptics.
+pub const HAPTICS_ENABLED: PrefKey<bool> = PrefKey::new("haptics_enabled", 0, || true);
+
+/// The user's preferred haptics style.
+pub const HAPTICS_STYLE: PrefKey<HapticsStyle> =
+    PrefKey::new("haptics_style", 0, HapticsStyle::default);
+
+/// The longest vibration played in the reduced style.
+pub const REDUCED_MAX_VIBRATION: Duration = Duration::from_millis(100);
+
+/// How strong haptic feedback should be.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HapticsStyle {
+    /// Play feedback as requested.
+    #[default]
+    Full,
+    /// Play every impact as `ImpactStyle::Light`, shorten vibrations to at most `REDUCED_MAX_VIBRATION` and skip selection feedback.
+    Reduced,
+}
+
+/// The kinds of haptic feedback which are throttled separately.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HapticKind {
+    Impact,
+    Notification,
+    Vibrate,
+    Selection,
+}
+
+/// Forwards calls to `Haptics`, unless the user has disabled haptics or the same kind of feedback was played too recently.
+///
+/// The enabled flag and style are loaded from preferences and saved whenever they are changed.
+/// Every method returns whether the feedback was played.
+#[derive(Clone, Debug)]
+pub struct HapticsController {
+    state: Rc<RefCell<ControllerState>>,
+}
+
+#[derive(Debug)]
+struct ControllerState {
+    enabled: bool,
+    style: HapticsStyle,
+    min_interval: Duration,
+    min_intervals: BTreeMap<HapticKind, Duration>,
+    /// When each kind of feedback was last played, in milliseconds since the unix epoch.
+    last_played: BTreeMap<HapticKind, f64>,
+}
+
+impl ControllerState {
+    fn new(enabled: bool, style: HapticsStyle, min_interval: Duration) -> Self {
+        Self {
+            enabled,
+            style,
+            min_interval,
+            min_intervals: Default::default(),
+            last_played: Default::default(),
+        }
+    }
+
+    /// Whether feedback of this kind may be played now. If so, records it as played.
+    fn try_play(&mut self, kind: HapticKind, now: f64) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let interval = self
+            .min_intervals
+            .get(&kind)
+            .copied()
+            .unwrap_or(self.min_interval)
+            .as_secs_f64()
+            * 1000.0;
+        if let Some(last) = self.last_played.get(&kind) {
+            if now - last < interval {
+                return false;
+            }
+        }
+        self.last_played.insert(kind, now);
+        true
+    }
+}
+
+impl HapticsController {
+    /// Load the haptics settings from preferences.
+    /// The same kind of feedback will not be played more than once per `min_interval`.
+    pub async fn load(min_interval: Duration) -> Result<Self, Error> {
+        let enabled = Preferences::get_key(&HAPTICS_ENABLED).await?;
+        let style = Preferences::get_key(&HAPTICS_STYLE).await?;
+        Ok(Self {
+            state: Rc::new(RefCell::new(ControllerState::new(
+                enabled,
+                style,
+                min_interval,
+            ))),
+        })
+    }
+
+    /// Use a different minimum interval for one kind of feedback.
+    pub fn set_min_interval(&self, kind: HapticKind, interval: Duration) {
+        self.state.borrow_mut().min_intervals.insert(kind, interval);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state.borrow().enabled
+    }
+
+    /// Enable or disable haptics and save the setting.
+    pub async fn set_enabled(&self, enabled: bool) -> Result<(), Error> {
+        self.state.borrow_mut().enabled = enabled;
+        Preferences::set_key(&HAPTICS_ENABLED, &enabled).await
+    }
+
+    pub fn style(&self) -> HapticsStyle {
+        self.state.borrow().style
+    }
+
+    /// Change the haptics style and save the setting.
+    pub async fn set_style(&self, style: HapticsStyle) -> Result<(), Error> {
+        self.state.borrow_mut().style = style;
+        Preferences::set_key(&HAPTICS_STYLE, &style).await
+    }
+
+    fn try_play(&self, kind: HapticKind) -> bool {
+        self.state.borrow_mut().try_play(kind, js_sys::Date::now())
+    }
+
+    /// Trigger a haptics "impact" feedback
+    pub async fn impact(&self, options: impl Into<ImpactOptions>) -> Result<bool, Error> {
+        if !self.try_play(HapticKind::Impact) {
+            return Ok(false);
+        }
+        let mut options = options.into();
+        if self.style() == HapticsStyle::Reduced {
+            options.style = ImpactStyle::Light;
+        }
+        Haptics::impact(options).await?;
+        Ok(true)
+    }
+
+    /// Trigger a haptics "notification" feedback
+    pub async fn notification(
+        &self,
+        options: impl Into<NotificationOptions>,
+    ) -> Result<bool, Error> {
+        if !self.try_play(HapticKind::Notification) {
+            return Ok(false);
+        }
+        Haptics::notification(options).await?;
+        Ok(true)
+    }
+
+    /// Vibrate the device
+    pub async fn vibrate(&self, options: impl Into<VibrateOptions>) -> Result<bool, Error> {
+        if !self.try_play(HapticKind::Vibrate) {
+            return Ok(false);
+        }
+        let mut options = options.into();
+        if self.style() == HapticsStyle::Reduced {
+            options.duration = options
+                .duration
+                .min(REDUCED_MAX_VIBRATION.as_secs_f64() * 1000.0);
+        }
+        Haptics::vibrate(options).await?;
+        Ok(true)
+    }
+
+    /// Trigger a haptics "selection changed" feedback
+    pub async fn selection_changed(&self) -> Result<bool, Error> {
+        if self.style() == HapticsStyle::Reduced || !self.try_play(HapticKind::Selection) {
+            return Ok(false);
+        }
+        Haptics::selection_changed().await?;
+        Ok(true)
+    }
+
+    /// Play a haptic pattern if haptics are enabled. Patterns are not throttled.
+    /// In the reduced style, the pattern's steps are adjusted in the same way as single feedback.
+    pub async fn play_pattern(&self, pattern: &HapticPattern) -> Result<bool, Error> {
+        if !self.is_enabled() {
+            return Ok(false);
+        }
+        match self.style() {
+            HapticsStyle::Full => pattern.play().await,
+            HapticsStyle::Reduced => reduce_pattern(pattern).play().await,
+        }
+    }
+}
+
+/// Play impacts as `ImpactStyle::Light`, shorten vibrations to at most `REDUCED_MAX_VIBRATION` and skip selection steps.
+fn reduce_pattern(pattern: &HapticPattern) -> HapticPattern {
+    pattern
+        .steps
+        .iter()
+        .filter_map(|step| match step {
+            HapticStep::Impact(_) => Some(HapticStep::Impact(ImpactStyle::Light)),
+            HapticStep::Vibrate(duration) => {
+                Some(HapticStep::Vibrate((*duration).min(REDUCED_MAX_VIBRATION)))
+            }
+            HapticStep::Selection => None,
+            HapticStep::Notification(_) | HapticStep::Pause(_) => Some(*step),
+        })
+        .collect::<Vec<_>>()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle() {
+        let mut state = ControllerState::new(true, HapticsStyle::Full, Duration::from_millis(100));
+        state
+            .min_intervals
+            .insert(HapticKind::Selection, Duration::from_millis(20));
+
+        assert!(state.try_play(HapticKind::Impact, 1000.0));
+        assert!(!state.try_play(HapticKind::Impact, 1050.0));
+        assert!(state.try_play(HapticKind::Notification, 1050.0));
+        assert!(state.try_play(HapticKind::Impact, 1100.0));
+
+        assert!(state.try_play(HapticKind::Selection, 1000.0));
+        assert!(state.try_play(HapticKind::Selection, 1020.0));
+        assert!(!state.try_play(HapticKind::Selection, 1030.0));
+
+        state.enabled = false;
+        assert!(!state.try_play(HapticKind::Vibrate, 5000.0));
+    }
+
+    #[test]
+    fn test_reduce_pattern() {
+        let pattern = HapticPattern::new()
+            .impact(ImpactStyle::Heavy)
+            .pause(Duration::from_millis(500))
+            .vibrate(Duration::from_millis(400))
+            .selection()
+            .notification(crate::haptics::NotificationType::Error);
+        assert_eq!(
+            reduce_pattern(&pattern),
+            HapticPattern::new()
+                .impact(ImpactStyle::Light)
+                .pause(Duration::from_millis(500))
+                .vibrate(REDUCED_MAX_VIBRATION)
+                .notification(crate::haptics::NotificationType::Error)
+        );
+    }
+}
@@ -1,8 +1,10 @@
-use crate::{error::Error, plugin_listener_handle::PluginListenerHandle};
+use crate::{
+    error::Error, extern_functions::set_timeout, plugin_listener_handle::PluginListenerHandle,
+};
 use js_sys::Promise;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
-use std::{future::Future, sync::Arc};
+use std::{future::Future, sync::Arc, time::Duration};
 use wasm_bindgen::{prelude::Closure, JsValue};
 use wasm_bindgen_futures::JsFuture;
 
@@ -96,8 +98,19 @@ pub async fn listen_async<T: serde::de::DeserializeOwned, F: Fn(T) + 'static>(
     func: F,
     name: &'static str,
     add_listener: impl Fn(&str, &Closure<dyn Fn(JsValue)>) -> JsValue,
+) -> Result<PluginListenerHandle, Error> {
+    listen_async_with(func, name, add_listener, |_| {}).await
+}
+
+/// Like `listen_async`, but `prepare` may adjust each event's javascript value before it is deserialized.
+pub(crate) async fn listen_async_with<T: serde::de::DeserializeOwned, F: Fn(T) + 'static>(
+    func: F,
+    name: &'static str,
+    add_listener: impl Fn(&str, &Closure<dyn Fn(JsValue)>) -> JsValue,
+    prepare: fn(&JsValue),
 ) -> Result<PluginListenerHandle, Error> {
     let func2 = move |js_value: JsValue| {
+        prepare(&js_value);
         let jv = js_value.clone();
 
         match serde_wasm_bindgen::from_value(js_value).map_err(|e| Error::deserializing::<T>(e)) {
@@ -118,3 +131,12 @@ pub async fn listen_async<T: serde::de::DeserializeOwned, F: Fn(T) + 'static>(
 
     Ok(PluginListenerHandle::new(closure, handle))
 }
+
+/// Wait for the given duration, using the javascript `setTimeout` function.
+pub async fn sleep(duration: Duration) {
+    let millis = duration.as_millis().min(i32::MAX as u128) as i32;
+    let promise = Promise::new(&mut |resolve, _reject| {
+        set_timeout(&resolve, millis);
+    });
+    let _ = JsFuture::from(promise).await;
+}
@@ -6,21 +6,37 @@ pub mod browser;
 pub mod camera;
 pub mod clipboard;
 pub mod device;
+pub mod device_profile;
 pub mod dialog;
+#[cfg(feature = "encryption")]
+pub mod encrypted_preferences;
 pub mod error;
 pub mod extern_functions;
 pub mod game_connect;
+pub mod haptic_pattern;
 pub mod haptics;
+pub mod haptics_controller;
 pub mod helpers;
 pub mod local_notifications;
+pub mod locale;
 pub mod network;
+pub mod network_monitor;
+pub mod notification_schedule;
+pub mod notification_scheduler;
+pub mod offline_queue;
 pub mod plugin_listener_handle;
+pub mod preference_keys;
 pub mod preferences;
+pub mod preferences_backup;
+pub mod preferences_cache;
+pub mod preferences_observer;
 pub mod rate;
 pub mod screen_reader;
 pub mod share;
 pub mod splash_screen;
 pub mod status_bar;
+pub mod system_theme;
+pub mod timestamp;
 pub mod toast;
 pub mod safe_area;
 
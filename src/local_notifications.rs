@@ -1,9 +1,12 @@
 use crate::extern_functions::*;
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
 use serde_with::skip_serializing_none;
 use typed_builder::TypedBuilder;
 
 use crate::helpers::*;
+use crate::timestamp::Timestamp;
 use crate::{error::Error, plugin_listener_handle::PluginListenerHandle};
 
 pub struct LocalNotifications;
@@ -40,6 +43,37 @@ impl LocalNotifications {
         .await
     }
 
+    /// Remove the delivered notifications with the given ids from the notifications screen.
+    /// Returns the ids which were removed.
+    pub async fn remove_delivered_by_ids(
+        ids: impl IntoIterator<Item = i32>,
+    ) -> Result<Vec<i32>, Error> {
+        let ids: Vec<i32> = ids.into_iter().collect();
+        Self::remove_delivered_where(|notification| ids.contains(&notification.id)).await
+    }
+
+    /// Remove every delivered notification in a group (Android) or thread (iOS) from the notifications screen.
+    /// Returns the ids which were removed.
+    pub async fn remove_delivered_group(group: &str) -> Result<Vec<i32>, Error> {
+        Self::remove_delivered_where(|notification| notification.group_key() == Some(group)).await
+    }
+
+    /// Remove the delivered notifications matching a predicate from the notifications screen.
+    /// Returns the ids which were removed.
+    pub async fn remove_delivered_where<F: Fn(&DeliveredNotificationSchema) -> bool>(
+        predicate: F,
+    ) -> Result<Vec<i32>, Error> {
+        let mut delivered = Self::get_delivered_notifications().await?;
+        delivered.notifications.retain(|x| predicate(x));
+        if delivered.notifications.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let ids = delivered.notifications.iter().map(|x| x.id).collect();
+        Self::remove_delivered_notifications(delivered).await?;
+        Ok(ids)
+    }
+
     /// Cancel pending notifications.
     pub async fn cancel(options: impl Into<CancelOptions>) -> Result<(), Error> {
         run_value_unit(options, local_notifications_cancel).await
@@ -52,7 +86,36 @@ impl LocalNotifications {
 
     /// Schedule one or more local notifications.
     pub async fn schedule(options: impl Into<ScheduleOptions>) -> Result<ScheduleResult, Error> {
-        run_value_value(options, local_notifications_schedule).await
+        let options: ScheduleOptions = options.into();
+        let js_value = to_js_value(&options)
+            .map_err(Error::serializing::<ScheduleOptions>)?;
+        convert_at_to_js_dates(&js_value);
+
+        let result = local_notifications_schedule(js_value).await?;
+        serde_wasm_bindgen::from_value(result).map_err(Error::deserializing::<ScheduleResult>)
+    }
+
+    /// Get a list of pending notifications.
+    pub async fn get_pending() -> Result<PendingResult, Error> {
+        let result = local_notifications_get_pending().await?;
+        convert_notifications_js_dates(&result);
+        serde_wasm_bindgen::from_value(result).map_err(Error::deserializing::<PendingResult>)
+    }
+
+    /// Check application setting for using exact alarms.
+    /// Only available for Android.
+    #[cfg(feature = "android")]
+    pub async fn check_exact_notification_setting() -> Result<SettingsPermissionStatus, Error> {
+        run_unit_value(local_notifications_check_exact_notification_setting).await
+    }
+
+    /// Direct user to the application settings screen to configure exact alarms.
+    /// In the event that a user changes the settings from granted to denied, the application will restart and any notification scheduled with an exact alarm will be deleted.
+    /// On Android < 12, the user will NOT be directed to the application settings screen, instead this function will return granted.
+    /// Only available for Android.
+    #[cfg(feature = "android")]
+    pub async fn change_exact_notification_setting() -> Result<SettingsPermissionStatus, Error> {
+        run_unit_value(local_notifications_change_exact_notification_setting).await
     }
 
     /// Register actions to take when notifications are displayed.
@@ -64,13 +127,35 @@ impl LocalNotifications {
         run_value_unit(options, local_notifications_register_action_types).await
     }
 
+    /// Create a notification channel.
+    /// Only available for Android 26+.
+    #[cfg(feature = "android")]
+    pub async fn create_channel(channel: impl Into<Channel>) -> Result<(), Error> {
+        run_value_unit(channel, local_notifications_create_channel).await
+    }
+
+    /// Delete a notification channel.
+    /// Only available for Android 26+.
+    #[cfg(feature = "android")]
+    pub async fn delete_channel(args: impl Into<DeleteChannelArgs>) -> Result<(), Error> {
+        run_value_unit(args, local_notifications_delete_channel).await
+    }
+
+    /// Get a list of notification channels.
+    /// Only available for Android 26+.
+    #[cfg(feature = "android")]
+    pub async fn list_channels() -> Result<ListChannelsResult, Error> {
+        run_unit_value(local_notifications_list_channels).await
+    }
+
     pub async fn add_received_listener<F: Fn(LocalNotificationSchema) + 'static>(
         func: F,
     ) -> Result<PluginListenerHandle, Error> {
-        listen_async(
+        listen_async_with(
             func,
             "localNotificationReceived",
             local_notifications_add_listener,
+            convert_js_dates,
         )
         .await
     }
@@ -78,15 +163,91 @@ impl LocalNotifications {
     pub async fn add_action_performed_listener<F: Fn(ActionPerformed) + 'static>(
         func: F,
     ) -> Result<PluginListenerHandle, Error> {
-        listen_async(
+        listen_async_with(
             func,
             "localNotificationActionPerformed",
             local_notifications_add_listener,
+            |action| {
+                if let Ok(notification) = js_sys::Reflect::get(action, &"notification".into()) {
+                    convert_js_dates(&notification);
+                }
+            },
         )
         .await
     }
 }
 
+/// Serialize notifications to javascript.
+/// Maps, such as `extra` payloads, become plain javascript objects rather than `Map`s, as the plugin expects.
+fn to_js_value<T: serde::Serialize>(
+    value: &T,
+) -> Result<wasm_bindgen::JsValue, serde_wasm_bindgen::Error> {
+    value.serialize(&serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true))
+}
+
+/// The web implementation expects `schedule.at` to be a javascript `Date`.
+/// Native platforms receive the date as an ISO string either way.
+fn convert_at_to_js_dates(options: &wasm_bindgen::JsValue) {
+    use js_sys::{Array, Reflect};
+    let Ok(notifications) = Reflect::get(options, &"notifications".into()) else {
+        return;
+    };
+    for notification in Array::from(&notifications).iter() {
+        let Ok(schedule) = Reflect::get(&notification, &"schedule".into()) else {
+            continue;
+        };
+        if !schedule.is_object() {
+            continue;
+        }
+        let Some(at) = Reflect::get(&schedule, &"at".into())
+            .ok()
+            .and_then(|x| x.as_string())
+        else {
+            continue;
+        };
+        if let Ok(at) = at.parse::<Timestamp>() {
+            let _ = Reflect::set(&schedule, &"at".into(), &at.to_js_date());
+        }
+    }
+}
+
+/// The web implementation returns `schedule.at` as a javascript `Date`, which deserializes as an empty object.
+/// Replace it with the number of milliseconds since the unix epoch.
+fn convert_js_dates(notification: &wasm_bindgen::JsValue) {
+    use js_sys::Reflect;
+    use wasm_bindgen::JsCast;
+    if !notification.is_object() {
+        return;
+    }
+    let Ok(schedule) = Reflect::get(notification, &"schedule".into()) else {
+        return;
+    };
+    if !schedule.is_object() {
+        return;
+    }
+    if let Ok(at) = Reflect::get(&schedule, &"at".into()) {
+        if let Some(date) = at.dyn_ref::<js_sys::Date>() {
+            let _ = Reflect::set(&schedule, &"at".into(), &date.get_time().into());
+        }
+    }
+}
+
+/// Apply `convert_js_dates` to every notification in a result.
+fn convert_notifications_js_dates(result: &wasm_bindgen::JsValue) {
+    use wasm_bindgen::JsCast;
+    if !result.is_object() {
+        return;
+    }
+    let Ok(notifications) = js_sys::Reflect::get(result, &"notifications".into()) else {
+        return;
+    };
+    if let Some(notifications) = notifications.dyn_ref::<js_sys::Array>() {
+        for notification in notifications.iter() {
+            convert_js_dates(&notification);
+        }
+    }
+}
+
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
 pub struct ScheduleOptions {
@@ -107,14 +268,122 @@ pub struct RegisterActionTypesOptions {
     pub types: Vec<ActionType>,
 }
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[skip_serializing_none]
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize, TypedBuilder)]
 #[serde(rename_all = "camelCase", default)]
 pub struct Action {
+    #[builder(setter(into))]
     /// The action identifier. Referenced in the 'actionPerformed' event as actionId.
     pub id: String,
 
+    #[builder(setter(into))]
     /// The title text to display for this action.
     pub title: String,
+
+    #[builder(setter(strip_option), default)]
+    /// Sets authenticationRequired in the options of UNNotificationAction. Only available for iOS.
+    pub requires_authentication: Option<bool>,
+
+    #[builder(setter(strip_option), default)]
+    /// Sets foreground in the options of UNNotificationAction. Only available for iOS.
+    pub foreground: Option<bool>,
+
+    #[builder(setter(strip_option), default)]
+    /// Sets destructive in the options of UNNotificationAction. Only available for iOS.
+    pub destructive: Option<bool>,
+
+    #[builder(setter(strip_option), default)]
+    /// Use a UNTextInputNotificationAction instead of a UNNotificationAction. The text entered is returned in `ActionPerformed::input_value`. Only available for iOS and Android.
+    pub input: Option<bool>,
+
+    #[builder(setter(into, strip_option), default)]
+    /// Sets textInputButtonTitle on UNTextInputNotificationAction. Only available for iOS when input is true.
+    pub input_button_title: Option<String>,
+
+    #[builder(setter(into, strip_option), default)]
+    /// Sets textInputPlaceholder on UNTextInputNotificationAction. Only available for iOS when input is true.
+    pub input_placeholder: Option<String>,
+}
+
+impl Action {
+    /// An action which lets the user type a reply.
+    pub fn text_input(
+        id: impl Into<String>,
+        title: impl Into<String>,
+        button_title: impl Into<String>,
+        placeholder: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            input: Some(true),
+            input_button_title: Some(button_title.into()),
+            input_placeholder: Some(placeholder.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Whether this action asks the user for text input.
+    pub fn is_input(&self) -> bool {
+        self.input == Some(true)
+    }
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PendingResult {
+    /// The list of pending notifications.
+    pub notifications: Vec<PendingLocalNotificationSchema>,
+}
+
+impl PendingResult {
+    /// The ids of the pending notifications.
+    pub fn ids(&self) -> Vec<i32> {
+        self.notifications.iter().map(|x| x.id).collect()
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PendingLocalNotificationSchema {
+    /// The notification identifier.
+    pub id: i32,
+    /// The title of the notification.
+    pub title: String,
+    /// The body of the notification, shown below the title.
+    pub body: String,
+    /// Schedule this notification for a later time.
+    pub schedule: Option<Schedule>,
+    /// Set extra data to store within this notification.
+    pub extra: Option<serde_json::Value>,
+}
+
+impl PendingLocalNotificationSchema {
+    /// Deserialize the extra data of this notification.
+    pub fn extra_as<T: DeserializeOwned>(&self) -> Result<Option<T>, Error> {
+        extra_from_json(&self.extra)
+    }
+}
+
+impl From<&PendingLocalNotificationSchema> for LocalNotificationDescriptor {
+    fn from(value: &PendingLocalNotificationSchema) -> Self {
+        Self { id: value.id }
+    }
+}
+
+impl From<PendingResult> for CancelOptions {
+    fn from(value: PendingResult) -> Self {
+        Self {
+            notifications: value.notifications.iter().map(|x| x.into()).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SettingsPermissionStatus {
+    /// Permission state of using exact alarms.
+    pub exact_alarm: PermissionState,
 }
 
 #[skip_serializing_none]
@@ -149,9 +418,23 @@ impl ActionPerformed {
     pub fn is_tap(&self) -> bool {
         self.action_id == "tap"
     }
+
+    /// The text entered by the user, if this is the given input action.
+    pub fn input_for(&self, action: &Action) -> Option<&str> {
+        if !action.is_input() || self.action_id != action.id {
+            return None;
+        }
+        self.input_value.as_deref()
+    }
+
+    /// Deserialize the extra data of the notification.
+    pub fn extra_as<T: DeserializeOwned>(&self) -> Result<Option<T>, Error> {
+        self.notification.extra_as()
+    }
 }
 
 /// A collection of actions.
+#[skip_serializing_none]
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
 pub struct ActionType {
@@ -160,6 +443,72 @@ pub struct ActionType {
 
     /// The list of actions associated with this action type.
     pub actions: Vec<Action>,
+
+    /// Sets hiddenPreviewsBodyPlaceholder of the UNNotificationCategory. Only available for iOS.
+    pub ios_hidden_previews_body_placeholder: Option<String>,
+
+    /// Sets customDismissAction in the options of the UNNotificationCategory. Only available for iOS.
+    pub ios_custom_dismiss_action: Option<bool>,
+
+    /// Sets allowInCarPlay in the options of the UNNotificationCategory. Only available for iOS.
+    pub ios_allow_in_car_play: Option<bool>,
+
+    /// Sets hiddenPreviewsShowTitle in the options of the UNNotificationCategory. Only available for iOS.
+    pub ios_hidden_previews_show_title: Option<bool>,
+
+    /// Sets hiddenPreviewsShowSubtitle in the options of the UNNotificationCategory. Only available for iOS.
+    pub ios_hidden_previews_show_subtitle: Option<bool>,
+}
+
+impl ActionType {
+    pub fn new(id: impl Into<String>, actions: Vec<Action>) -> Self {
+        Self {
+            id: id.into(),
+            actions,
+            ..Default::default()
+        }
+    }
+}
+
+/// Represents a notification attachment.
+#[skip_serializing_none]
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Attachment {
+    /// The attachment identifier.
+    pub id: String,
+    /// The URL to the attachment. Use the res scheme to reference web assets, e.g. res:///assets/img/icon.png. Also accepts file URLs.
+    pub url: String,
+    /// Attachment options.
+    pub options: Option<AttachmentOptions>,
+}
+
+impl Attachment {
+    pub fn new(id: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            url: url.into(),
+            options: None,
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttachmentOptions {
+    /// Sets the UNNotificationAttachmentOptionsTypeHintKey key in the hashable options of UNNotificationAttachment. Only available for iOS.
+    #[serde(rename = "iosUNNotificationAttachmentOptionsTypeHintKey")]
+    pub ios_type_hint_key: Option<String>,
+    /// Sets the UNNotificationAttachmentOptionsThumbnailHiddenKey key in the hashable options of UNNotificationAttachment. Only available for iOS.
+    #[serde(rename = "iosUNNotificationAttachmentOptionsThumbnailHiddenKey")]
+    pub ios_thumbnail_hidden_key: Option<String>,
+    /// Sets the UNNotificationAttachmentOptionsThumbnailClippingRectKey key in the hashable options of UNNotificationAttachment. Only available for iOS.
+    #[serde(rename = "iosUNNotificationAttachmentOptionsThumbnailClippingRectKey")]
+    pub ios_thumbnail_clipping_rect_key: Option<String>,
+    /// Sets the UNNotificationAttachmentOptionsThumbnailTimeKey key in the hashable options of UNNotificationAttachment. Only available for iOS.
+    #[serde(rename = "iosUNNotificationAttachmentOptionsThumbnailTimeKey")]
+    pub ios_thumbnail_time_key: Option<String>,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
@@ -207,6 +556,8 @@ impl Default for LocalNotificationSchema {
             thread_identifier: Default::default(),
             summary_argument: Default::default(),
             channel_id: Default::default(),
+            extra: Default::default(),
+            attachments: Default::default(),
         }
     }
 }
@@ -270,6 +621,46 @@ pub struct LocalNotificationSchema {
     #[builder(setter(into, strip_option), default)]
     /// Specifies the channel the notification should be delivered on. If channel with the given name does not exist then the notification will not fire. If not provided, it will use the default channel. Calls setChannelId() on NotificationCompat.Builder with the provided value. Only available for Android 26+.
     pub channel_id: Option<String>,
+    #[builder(setter(strip_option), default)]
+    /// Set extra data to store within this notification.
+    pub extra: Option<serde_json::Value>,
+    #[builder(setter(into, strip_option), default)]
+    /// Set a list of attachments, such as images, for this notification. Only available for iOS.
+    pub attachments: Option<Vec<Attachment>>,
+}
+
+impl LocalNotificationSchema {
+    /// Serialize a value and store it as the extra data of this notification.
+    pub fn with_extra<T: Serialize>(mut self, extra: &T) -> Result<Self, Error> {
+        self.extra = Some(extra_to_json(extra)?);
+        Ok(self)
+    }
+
+    /// Deserialize the extra data of this notification.
+    pub fn extra_as<T: DeserializeOwned>(&self) -> Result<Option<T>, Error> {
+        extra_from_json(&self.extra)
+    }
+}
+
+fn extra_to_json<T: Serialize>(extra: &T) -> Result<serde_json::Value, Error> {
+    serde_json::to_value(extra).map_err(|error| Error::JsonSerializeError {
+        typename: std::any::type_name::<T>(),
+        error,
+    })
+}
+
+fn extra_from_json<T: DeserializeOwned>(
+    extra: &Option<serde_json::Value>,
+) -> Result<Option<T>, Error> {
+    match extra {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => T::deserialize(value)
+            .map(Some)
+            .map_err(|error| Error::JsonDeserializeError {
+                typename: std::any::type_name::<T>(),
+                error,
+            }),
+    }
 }
 
 
@@ -280,31 +671,20 @@ pub enum Schedule {
     On {
         /// Schedule a notification on particular interval(s). This is similar to scheduling cron jobs. Only available for iOS and Android.
         on: ScheduleOn,
-        #[serde(rename = "allowWhileIdle")]
+        #[serde(rename = "allowWhileIdle", default)]
         /// Allow this notification to fire while in Doze Only available for Android 23+. Note that these notifications can only fire once per 9 minutes, per app.
         allow_while_idle: bool,
     },
     At {
-        #[serde(
-            skip_deserializing,
-            default = "js_sys::Date::new_0",
-            serialize_with = "serde_wasm_bindgen::preserve::serialize"
-        )]
-
-
-        /// WARNING: This will not be deserialized correctly
-        ///
         /// Schedule a notification at a specific date and time.
         /// ```ignore
-        /// let time: chrono::DateTime<chrono::Utc> = chrono::Utc::now() + Duration::seconds(5); //notify 5 seconds from now
-        /// let time_string = time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
-        /// let at_millis = JsValue::from_f64(js_sys::Date::parse(&time_string));
-        /// let at = js_sys::Date::new(&at_millis);
+        /// let at = Timestamp::now() + std::time::Duration::from_secs(5); //notify 5 seconds from now
         /// ```
-        at: js_sys::Date,
-
+        at: Timestamp,
+        #[serde(default)]
         /// Repeat delivery of this notification at the date and time specified by at. Only available for iOS and Android.
         repeats: bool,
+        #[serde(rename = "allowWhileIdle", default)]
         /// Allow this notification to fire while in Doze Only available for Android 23+. Note that these notifications can only fire once per 9 minutes, per app.
         allow_while_idle: bool,
     },
@@ -313,7 +693,7 @@ pub enum Schedule {
         every: ScheduleEvery,
         /// Limit the number times a notification is delivered by the interval specified by every.
         count: usize,
-        #[serde(rename = "allowWhileIdle")]
+        #[serde(rename = "allowWhileIdle", default)]
         /// Allow this notification to fire while in Doze Only available for Android 23+. Note that these notifications can only fire once per 9 minutes, per app.
         allow_while_idle: bool,
     },
@@ -371,6 +751,82 @@ pub struct ScheduleOn {
     pub second: Option<u32>,
 }
 
+#[skip_serializing_none]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TypedBuilder)]
+#[serde(rename_all = "camelCase")]
+pub struct Channel {
+    #[builder(setter(into))]
+    /// The channel identifier.
+    pub id: String,
+    #[builder(setter(into))]
+    /// The human-friendly name of this channel (presented to the user).
+    pub name: String,
+    #[builder(setter(into, strip_option), default)]
+    /// The description of this channel (presented to the user).
+    pub description: Option<String>,
+    #[builder(setter(into, strip_option), default)]
+    /// The sound that should be played for notifications posted to this channel. Notification channels with an importance of at least 3 should have a sound. The file name of a sound file should be specified relative to the android app res/raw directory. If the sound is not provided, or the sound file is not found no sound will be used.
+    pub sound: Option<String>,
+    #[builder(setter(strip_option), default)]
+    /// The level of interruption for notifications posted to this channel.
+    pub importance: Option<Importance>,
+    #[builder(setter(strip_option), default)]
+    /// The visibility of notifications posted to this channel. This setting is for whether notifications posted to this channel appear on the lockscreen or not, and if so, whether they appear in a redacted form.
+    pub visibility: Option<Visibility>,
+    #[builder(setter(strip_option), default)]
+    /// Whether notifications posted to this channel should display notification lights, on devices that support it.
+    pub lights: Option<bool>,
+    #[builder(setter(into, strip_option), default)]
+    /// The light color for notifications posted to this channel. Only supported if lights are enabled on this channel and the device supports it. Supported color formats are #RRGGBB and #RRGGBBAA.
+    pub light_color: Option<String>,
+    #[builder(setter(strip_option), default)]
+    /// Whether notifications posted to this channel should vibrate.
+    pub vibration: Option<bool>,
+}
+
+/// The importance level. For more details, see the Android Developer Docs.
+#[derive(Serialize_repr, Deserialize_repr, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum Importance {
+    Min = 1,
+    Low = 2,
+    Default = 3,
+    High = 4,
+    Max = 5,
+}
+
+/// The notification visibility. For more details, see the Android Developer Docs.
+#[derive(Serialize_repr, Deserialize_repr, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(i8)]
+pub enum Visibility {
+    /// Do not reveal any part of this notification on a secure lockscreen.
+    Secret = -1,
+    /// Show this notification on all lockscreens, but conceal sensitive or private information on secure lockscreens.
+    Private = 0,
+    /// Show this notification in its entirety on all lockscreens.
+    Public = 1,
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteChannelArgs {
+    /// The channel identifier.
+    pub id: String,
+}
+
+impl From<&str> for DeleteChannelArgs {
+    fn from(id: &str) -> Self {
+        Self { id: id.to_string() }
+    }
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ListChannelsResult {
+    /// The list of notification channels.
+    pub channels: Vec<Channel>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 #[repr(u8)]
@@ -418,9 +874,50 @@ pub struct DeliveredNotificationSchema {
     pub group: Option<String>,
     /// If this notification is the summary for a group of notifications. Only available for Android.
     pub group_summary: Option<bool>,
-    //pub data: Any,
-    //pub extra: Any,
-    //TODO additional fields
+    /// Any additional data that was included in the notification payload. Only available for Android.
+    pub data: Option<serde_json::Value>,
+    /// Extra data to store within this notification. Only available for iOS.
+    pub extra: Option<serde_json::Value>,
+    /// The attachments for this notification. Only available for iOS.
+    pub attachments: Option<Vec<Attachment>>,
+    /// Action type associated with this notification. Only available for iOS.
+    pub action_type_id: Option<String>,
+    /// Schedule used to fire this notification. Only available for iOS.
+    pub schedule: Option<Schedule>,
+    /// Sound that was used when the notification was displayed. Only available for iOS.
+    pub sound: Option<String>,
+    /// The thread identifier used to group the notification. Only available for iOS.
+    pub thread_identifier: Option<String>,
+}
+
+impl DeliveredNotificationSchema {
+    /// Deserialize the extra data of this notification.
+    pub fn extra_as<T: DeserializeOwned>(&self) -> Result<Option<T>, Error> {
+        extra_from_json(&self.extra)
+    }
+
+    /// The group of this notification on Android, or its thread identifier on iOS.
+    pub fn group_key(&self) -> Option<&str> {
+        self.group
+            .as_deref()
+            .or(self.thread_identifier.as_deref())
+    }
+}
+
+impl DeliveredNotifications {
+    /// The notifications, grouped by `group` on Android or `thread_identifier` on iOS.
+    /// Notifications without a group are under `None`.
+    pub fn grouped(&self) -> BTreeMap<Option<&str>, Vec<&DeliveredNotificationSchema>> {
+        let mut groups: BTreeMap<Option<&str>, Vec<&DeliveredNotificationSchema>> =
+            BTreeMap::new();
+        for notification in self.notifications.iter() {
+            groups
+                .entry(notification.group_key())
+                .or_default()
+                .push(notification);
+        }
+        groups
+    }
 }
 
 #[cfg(test)]
@@ -533,4 +1030,124 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    fn test_channel_json() {
+        let channel = Channel::builder()
+            .id("alerts")
+            .name("Alerts")
+            .importance(Importance::High)
+            .visibility(Visibility::Secret)
+            .build();
+
+        let str = serde_json::to_string(&channel).unwrap();
+        assert_eq!(
+            str,
+            "{\"id\":\"alerts\",\"name\":\"Alerts\",\"importance\":4,\"visibility\":-1}"
+        );
+        assert_eq!(serde_json::from_str::<Channel>(&str).unwrap(), channel);
+    }
+
+    #[test]
+    fn test_extra() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Route {
+            page: String,
+            item: u32,
+        }
+
+        let route = Route {
+            page: "orders".to_string(),
+            item: 42,
+        };
+        let schema = LocalNotificationSchema::builder()
+            .title("Title")
+            .body("Body")
+            .schedule(ScheduleOn::builder().second(0).build())
+            .id(1)
+            .auto_cancel(true)
+            .build()
+            .with_extra(&route)
+            .unwrap();
+
+        let json = serde_json::to_string(&schema).unwrap();
+        assert!(json.ends_with(",\"extra\":{\"item\":42,\"page\":\"orders\"}}"));
+
+        let performed = ActionPerformed {
+            action_id: "tap".to_string(),
+            input_value: None,
+            notification: serde_json::from_str(&json).unwrap(),
+        };
+        assert_eq!(performed.extra_as::<Route>().unwrap(), Some(route));
+        assert!(performed.extra_as::<u32>().is_err());
+        assert_eq!(
+            LocalNotificationSchema::default()
+                .extra_as::<Route>()
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_input_action() {
+        let reply = Action::text_input("reply", "Reply", "Send", "Message");
+        let like = Action::builder().id("like").title("Like").build();
+        assert_eq!(
+            serde_json::to_string(&reply).unwrap(),
+            "{\"id\":\"reply\",\"title\":\"Reply\",\"input\":true,\"inputButtonTitle\":\"Send\",\"inputPlaceholder\":\"Message\"}"
+        );
+
+        let performed = ActionPerformed {
+            action_id: "reply".to_string(),
+            input_value: Some("On my way".to_string()),
+            notification: Default::default(),
+        };
+        assert_eq!(performed.input_for(&reply), Some("On my way"));
+        assert_eq!(performed.input_for(&like), None);
+    }
+
+    #[test]
+    fn test_delivered_grouped() {
+        let delivered: DeliveredNotifications = serde_json::from_str(
+            r#"{"notifications":[
+                {"id":1,"title":"a","body":"","group":"chat-1"},
+                {"id":2,"title":"b","body":"","threadIdentifier":"chat-2","schedule":{"at":"2024-03-01T09:30:00.000Z","repeats":false}},
+                {"id":3,"title":"c","body":"","group":"chat-1"},
+                {"id":4,"title":"d","body":""}
+            ]}"#,
+        )
+        .unwrap();
+
+        let grouped = delivered.grouped();
+        let ids = |key| -> Vec<i32> { grouped[&key].iter().map(|x| x.id).collect() };
+        assert_eq!(grouped.len(), 3);
+        assert_eq!(ids(Some("chat-1")), [1, 3]);
+        assert_eq!(ids(Some("chat-2")), [2]);
+        assert_eq!(ids(None), [4]);
+        assert!(matches!(
+            delivered.notifications[1].schedule,
+            Some(Schedule::At { .. })
+        ));
+    }
+
+    #[test]
+    fn test_pending_at_millis() {
+        // `convert_js_dates` replaces the javascript `Date` returned on web with milliseconds since the unix epoch
+        let pending: PendingResult = serde_json::from_str(
+            r#"{"notifications":[
+                {"id":1,"title":"t","body":"b","schedule":{"at":1709285400000}},
+                {"id":2,"title":"t","body":"b","schedule":{"at":1709285400000.0,"repeats":true}},
+                {"id":3,"title":"t","body":"b","schedule":{"at":"2024-03-01T09:30:00.000Z"}}
+            ]}"#,
+        )
+        .unwrap();
+
+        let expected: Timestamp = "2024-03-01T09:30:00Z".parse().unwrap();
+        for notification in pending.notifications {
+            match notification.schedule {
+                Some(Schedule::At { at, .. }) => assert_eq!(at, expected),
+                other => panic!("Expected Schedule::At but got {other:?}"),
+            }
+        }
+    }
 }
@@ -0,0 +1,322 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::device::{GetLanguageCodeResult, LanguageTag};
+
+/// A BCP 47 language tag, parsed into its language, script, region and variant subtags.
+/// Extension and private use subtags are ignored.
+#[derive(Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Locale {
+    /// The primary language subtag, in lowercase e.g. "pt".
+    pub language: String,
+    /// The script subtag, in titlecase e.g. "Hant".
+    pub script: Option<String>,
+    /// The region subtag, in uppercase e.g. "BR" or "419".
+    pub region: Option<String>,
+    /// The variant subtags, in lowercase e.g. "valencia".
+    pub variants: Vec<String>,
+}
+
+/// The error returned when a string is not a well-formed language tag.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseLocaleError {
+    pub tag: String,
+}
+
+impl Display for ParseLocaleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid language tag: '{}'", self.tag)
+    }
+}
+
+impl std::error::Error for ParseLocaleError {}
+
+impl FromStr for Locale {
+    type Err = ParseLocaleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseLocaleError { tag: s.to_string() };
+        // Android and some browsers use underscores as separators
+        let mut subtags = s.trim().split(['-', '_']).peekable();
+
+        let language = subtags
+            .next()
+            .filter(|x| (2..=8).contains(&x.len()) && x.chars().all(|c| c.is_ascii_alphabetic()))
+            .ok_or_else(error)?
+            .to_ascii_lowercase();
+
+        // Extended language subtags are skipped
+        while subtags
+            .next_if(|x| x.len() == 3 && x.chars().all(|c| c.is_ascii_alphabetic()))
+            .is_some()
+        {}
+
+        let script = subtags
+            .next_if(|x| x.len() == 4 && x.chars().all(|c| c.is_ascii_alphabetic()))
+            .map(to_title_case);
+
+        let region = subtags
+            .next_if(|x| {
+                (x.len() == 2 && x.chars().all(|c| c.is_ascii_alphabetic()))
+                    || (x.len() == 3 && x.chars().all(|c| c.is_ascii_digit()))
+            })
+            .map(|x| x.to_ascii_uppercase());
+
+        let mut variants = vec![];
+        while let Some(variant) = subtags.next_if(|x| is_variant(x)) {
+            variants.push(variant.to_ascii_lowercase());
+        }
+
+        // Anything left over must be an extension or private use sequence
+        if let Some(rest) = subtags.peek() {
+            if rest.len() != 1 || !rest.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(error());
+            }
+        }
+
+        Ok(Self {
+            language,
+            script,
+            region,
+            variants,
+        })
+    }
+}
+
+fn is_variant(subtag: &str) -> bool {
+    let alphanumeric = subtag.chars().all(|c| c.is_ascii_alphanumeric());
+    match subtag.len() {
+        5..=8 => alphanumeric,
+        4 => alphanumeric && subtag.starts_with(|c: char| c.is_ascii_digit()),
+        _ => false,
+    }
+}
+
+fn to_title_case(subtag: &str) -> String {
+    subtag
+        .char_indices()
+        .map(|(i, c)| {
+            if i == 0 {
+                c.to_ascii_uppercase()
+            } else {
+                c.to_ascii_lowercase()
+            }
+        })
+        .collect()
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.language)?;
+        if let Some(script) = &self.script {
+            write!(f, "-{script}")?;
+        }
+        if let Some(region) = &self.region {
+            write!(f, "-{region}")?;
+        }
+        for variant in self.variants.iter() {
+            write!(f, "-{variant}")?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for Locale {
+    type Error = ParseLocaleError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Locale> for String {
+    fn from(value: Locale) -> Self {
+        value.to_string()
+    }
+}
+
+impl Locale {
+    /// The locale with only the language subtag e.g. "pt-BR" becomes "pt".
+    pub fn language_only(&self) -> Self {
+        Self {
+            language: self.language.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// The script subtag or, if there isn't one, the script implied by the language and region.
+    /// For example "zh-TW" implies "Hant" and "zh-CN" implies "Hans".
+    pub fn likely_script(&self) -> Option<&str> {
+        if let Some(script) = &self.script {
+            return Some(script);
+        }
+        match (self.language.as_str(), self.region.as_deref()?) {
+            ("zh", "TW" | "HK" | "MO") => Some("Hant"),
+            ("zh", "CN" | "SG" | "MY") => Some("Hans"),
+            _ => None,
+        }
+    }
+
+    /// Choose the supported locale which best matches this one.
+    /// Exact matches are preferred, then locales with fewer subtags (e.g. "pt-BR" falls back to "pt"), then locales with the same language but a different region (e.g. "pt-PT").
+    /// Locales with a different language or script never match. Missing scripts are inferred with `likely_script`, so "zh-TW" matches "zh-Hant" rather than "zh-Hans".
+    /// If several locales match equally well, the first is returned.
+    pub fn best_match<'a>(
+        &self,
+        supported: impl IntoIterator<Item = &'a Locale>,
+    ) -> Option<&'a Locale> {
+        let mut best: Option<(u32, &'a Locale)> = None;
+        for locale in supported {
+            if let Some(score) = self.match_score(locale) {
+                if best.is_none_or(|(best_score, _)| score > best_score) {
+                    best = Some((score, locale));
+                }
+            }
+        }
+        best.map(|(_, locale)| locale)
+    }
+
+    /// Choose the supported language tag which best matches this locale, using the rules of `best_match`.
+    /// Supported tags which cannot be parsed are ignored.
+    pub fn negotiate<'a, T: AsRef<str>>(&self, supported: &'a [T]) -> Option<&'a T> {
+        let parsed: Vec<(Locale, &'a T)> = supported
+            .iter()
+            .filter_map(|tag| tag.as_ref().parse().ok().map(|locale| (locale, tag)))
+            .collect();
+
+        let best = self.best_match(parsed.iter().map(|(locale, _)| locale))?;
+        parsed
+            .iter()
+            .find(|(locale, _)| std::ptr::eq(locale, best))
+            .map(|(_, tag)| *tag)
+    }
+
+    fn match_score(&self, supported: &Locale) -> Option<u32> {
+        if self.language != supported.language {
+            return None;
+        }
+
+        let region_score = match (&self.region, &supported.region) {
+            (Some(a), Some(b)) if a == b => 20,
+            (_, None) => 10,
+            (None, Some(_)) => 5,
+            (Some(_), Some(_)) => 0,
+        };
+
+        let script_score = match (self.likely_script(), supported.likely_script()) {
+            (Some(a), Some(b)) if a == b => 4,
+            (_, None) => 2,
+            (None, Some(_)) => 1,
+            (Some(_), Some(_)) => return None,
+        };
+
+        if !supported.variants.iter().all(|v| self.variants.contains(v)) {
+            return None;
+        }
+        let variant_score = supported.variants.len() as u32;
+
+        Some(region_score + script_score + variant_score)
+    }
+}
+
+impl LanguageTag {
+    /// Parse this language tag.
+    pub fn locale(&self) -> Result<Locale, ParseLocaleError> {
+        self.value.parse()
+    }
+
+    /// Choose the supported language tag which best matches this one.
+    /// Returns `None` if this tag cannot be parsed or nothing matches.
+    pub fn negotiate<'a, T: AsRef<str>>(&self, supported: &'a [T]) -> Option<&'a T> {
+        self.locale().ok()?.negotiate(supported)
+    }
+}
+
+impl GetLanguageCodeResult {
+    /// Parse this language code.
+    pub fn locale(&self) -> Result<Locale, ParseLocaleError> {
+        self.value.parse()
+    }
+
+    /// Choose the supported language tag which best matches this language code.
+    /// Returns `None` if this code cannot be parsed or nothing matches.
+    pub fn negotiate<'a, T: AsRef<str>>(&self, supported: &'a [T]) -> Option<&'a T> {
+        self.locale().ok()?.negotiate(supported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locale(s: &str) -> Locale {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            locale("zh_hant_tw"),
+            Locale {
+                language: "zh".to_string(),
+                script: Some("Hant".to_string()),
+                region: Some("TW".to_string()),
+                variants: vec![],
+            }
+        );
+        assert_eq!(locale("es-419").region.as_deref(), Some("419"));
+        assert_eq!(locale("ca-ES-valencia").variants, vec!["valencia"]);
+        assert_eq!(locale("en-US-u-ca-gregory").to_string(), "en-US");
+        assert_eq!(locale("zh-yue-HK").to_string(), "zh-HK");
+
+        assert!("".parse::<Locale>().is_err());
+        assert!("e".parse::<Locale>().is_err());
+        assert!("en-US-!".parse::<Locale>().is_err());
+        assert!("en-toolongsubtag".parse::<Locale>().is_err());
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let supported = ["en", "en-GB", "pt-PT", "pt", "zh-Hans", "zh-Hant"];
+
+        assert_eq!(locale("en-GB").negotiate(&supported), Some(&"en-GB"));
+        assert_eq!(locale("en-US").negotiate(&supported), Some(&"en"));
+        assert_eq!(locale("pt-BR").negotiate(&supported), Some(&"pt"));
+        assert_eq!(locale("pt-PT").negotiate(&supported), Some(&"pt-PT"));
+        assert_eq!(locale("zh-Hant-HK").negotiate(&supported), Some(&"zh-Hant"));
+        assert_eq!(locale("fr-FR").negotiate(&supported), None);
+
+        assert_eq!(locale("pt-BR").negotiate(&["pt-PT", "es"]), Some(&"pt-PT"));
+    }
+
+    #[test]
+    fn test_likely_script() {
+        let supported = ["zh-Hans", "zh-Hant"];
+        assert_eq!(locale("zh-TW").negotiate(&supported), Some(&"zh-Hant"));
+        assert_eq!(locale("zh-HK").negotiate(&supported), Some(&"zh-Hant"));
+        assert_eq!(locale("zh-CN").negotiate(&supported), Some(&"zh-Hans"));
+        assert_eq!(locale("zh-SG").negotiate(&supported), Some(&"zh-Hans"));
+
+        assert_eq!(
+            locale("zh-Hant-HK").negotiate(&["zh-CN", "zh-TW"]),
+            Some(&"zh-TW")
+        );
+        assert_eq!(locale("zh-MO").negotiate(&["zh", "zh-CN"]), Some(&"zh"));
+        assert_eq!(locale("zh").likely_script(), None);
+    }
+
+    #[test]
+    fn test_language_tag() {
+        let tag = LanguageTag {
+            value: "pt-BR".to_string(),
+        };
+        assert_eq!(tag.negotiate(&["en", "pt"]), Some(&"pt"));
+
+        let code = GetLanguageCodeResult {
+            value: "de".to_string(),
+        };
+        assert_eq!(code.negotiate(&["en", "de-DE"]), Some(&"de-DE"));
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+use crate::error::Error;
+use crate::helpers::sleep;
+use crate::network::{ConnectionStatus, Network};
+use crate::plugin_listener_handle::PluginListenerHandle;
+
+type Subscriber = Rc<dyn Fn(&ConnectionStatus)>;
+
+/// Tracks the network status, smoothing over the rapid and redundant changes reported while switching networks.
+///
+/// A change is only reported once the status has stayed the same for the debounce window,
+/// and only if it differs from the last reported status.
+#[derive(Clone)]
+pub struct NetworkMonitor {
+    state: Rc<RefCell<MonitorState>>,
+}
+
+struct MonitorState {
+    current: ConnectionStatus,
+    debounce: Duration,
+    /// Incremented on every raw change so that superseded changes can be ignored.
+    generation: u64,
+    next_subscriber_id: u64,
+    subscribers: Vec<(u64, Subscriber)>,
+    listener: Option<PluginListenerHandle>,
+}
+
+impl MonitorState {
+    fn new(current: ConnectionStatus, debounce: Duration) -> Self {
+        Self {
+            current,
+            debounce,
+            generation: 0,
+            next_subscriber_id: 0,
+            subscribers: vec![],
+            listener: None,
+        }
+    }
+
+    /// Start a new generation, superseding any changes still waiting to settle.
+    fn next_generation(&mut self) -> u64 {
+        self.generation += 1;
+        self.generation
+    }
+
+    fn remove_subscriber(&mut self, id: u64) {
+        self.subscribers.retain(|(x, _)| *x != id);
+    }
+}
+
+impl Drop for MonitorState {
+    fn drop(&mut self) {
+        if let Some(listener) = self.listener.take() {
+            wasm_bindgen_futures::spawn_local(async move {
+                let _ = listener.remove_async().await;
+            });
+        }
+    }
+}
+
+impl std::fmt::Debug for NetworkMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("NetworkMonitor")
+            .field("current", &state.current)
+            .field("debounce", &state.debounce)
+            .finish()
+    }
+}
+
+impl NetworkMonitor {
+    /// Get the current network status and start listening for changes.
+    pub async fn start(debounce: Duration) -> Result<Self, Error> {
+        let current = Network::get_status().await?;
+        let state = Rc::new(RefCell::new(MonitorState::new(current, debounce)));
+
+        let weak = Rc::downgrade(&state);
+        let listener = Network::add_network_change_listener(move |status| {
+            if let Some(state) = weak.upgrade() {
+                Self { state }.on_raw_change(status);
+            }
+        })
+        .await?;
+        state.borrow_mut().listener = Some(listener);
+
+        Ok(Self { state })
+    }
+
+    /// The most recently reported network status.
+    pub fn current(&self) -> ConnectionStatus {
+        self.state.borrow().current.clone()
+    }
+
+    /// Whether the most recently reported network status is connected.
+    pub fn is_connected(&self) -> bool {
+        self.state.borrow().current.connected
+    }
+
+    /// Call a function whenever the reported network status changes.
+    pub fn subscribe<F: Fn(&ConnectionStatus) + 'static>(&self, func: F) -> NetworkSubscription {
+        let mut state = self.state.borrow_mut();
+        let id = state.next_subscriber_id;
+        state.next_subscriber_id += 1;
+        state.subscribers.push((id, Rc::new(func)));
+        NetworkSubscription {
+            id,
+            state: Rc::downgrade(&self.state),
+        }
+    }
+
+    fn on_raw_change(&self, status: ConnectionStatus) {
+        let (generation, debounce) = {
+            let mut state = self.state.borrow_mut();
+            (state.next_generation(), state.debounce)
+        };
+
+        let weak = Rc::downgrade(&self.state);
+        wasm_bindgen_futures::spawn_local(async move {
+            if !debounce.is_zero() {
+                sleep(debounce).await;
+            }
+            if let Some(state) = weak.upgrade() {
+                Self { state }.settle(generation, status);
+            }
+        });
+    }
+
+    /// Report a status if no newer raw change has arrived and it differs from the current status.
+    fn settle(&self, generation: u64, status: ConnectionStatus) {
+        let subscribers: Vec<Subscriber> = {
+            let mut state = self.state.borrow_mut();
+            if state.generation != generation || state.current == status {
+                return;
+            }
+            state.current = status.clone();
+            state.subscribers.iter().map(|(_, s)| s.clone()).collect()
+        };
+
+        for subscriber in subscribers {
+            subscriber(&status);
+        }
+    }
+}
+
+/// A handle for a `NetworkMonitor` subscription.
+/// If this is dropped, the subscription will be removed, so either store it somewhere or call `leak`.
+#[must_use = "Handle must not be dropped unless the subscription is no longer needed"]
+pub struct NetworkSubscription {
+    id: u64,
+    state: Weak<RefCell<MonitorState>>,
+}
+
+impl std::fmt::Debug for NetworkSubscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NetworkSubscription")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl NetworkSubscription {
+    /// Leak this subscription so it will never be removed
+    pub fn leak(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for NetworkSubscription {
+    fn drop(&mut self) {
+        let Some(state) = self.state.upgrade() else {
+            return;
+        };
+        let id = self.id;
+        if let Ok(mut state) = state.try_borrow_mut() {
+            state.remove_subscriber(id);
+            return;
+        }
+        // The monitor is in use, so remove the subscriber once it is free
+        let weak = self.state.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Some(state) = weak.upgrade() {
+                state.borrow_mut().remove_subscriber(id);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::ConnectionType;
+
+    fn status(connected: bool, connection_type: ConnectionType) -> ConnectionStatus {
+        ConnectionStatus {
+            connected,
+            connection_type,
+        }
+    }
+
+    fn monitor() -> (NetworkMonitor, Rc<RefCell<Vec<ConnectionStatus>>>) {
+        let monitor = NetworkMonitor {
+            state: Rc::new(RefCell::new(MonitorState::new(
+                status(true, ConnectionType::Wifi),
+                Duration::ZERO,
+            ))),
+        };
+        let reported = Rc::new(RefCell::new(vec![]));
+        let reported_clone = reported.clone();
+        monitor
+            .subscribe(move |status| reported_clone.borrow_mut().push(status.clone()))
+            .leak();
+        (monitor, reported)
+    }
+
+    #[test]
+    fn test_superseded_generation() {
+        let (monitor, reported) = monitor();
+        let first = monitor.state.borrow_mut().next_generation();
+        let second = monitor.state.borrow_mut().next_generation();
+
+        monitor.settle(first, status(false, ConnectionType::None));
+        assert!(reported.borrow().is_empty());
+        assert!(monitor.is_connected());
+
+        monitor.settle(second, status(true, ConnectionType::Cellular));
+        assert_eq!(
+            reported.borrow().as_slice(),
+            [status(true, ConnectionType::Cellular)]
+        );
+        assert_eq!(monitor.current(), status(true, ConnectionType::Cellular));
+    }
+
+    #[test]
+    fn test_duplicate_status() {
+        let (monitor, reported) = monitor();
+        let generation = monitor.state.borrow_mut().next_generation();
+        monitor.settle(generation, status(true, ConnectionType::Wifi));
+        assert!(reported.borrow().is_empty());
+
+        let generation = monitor.state.borrow_mut().next_generation();
+        monitor.settle(generation, status(false, ConnectionType::None));
+        let generation = monitor.state.borrow_mut().next_generation();
+        monitor.settle(generation, status(false, ConnectionType::None));
+        assert_eq!(reported.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_unsubscribe() {
+        let (monitor, reported) = monitor();
+        let other = Rc::new(RefCell::new(0));
+        let other_clone = other.clone();
+        let subscription = monitor.subscribe(move |_| *other_clone.borrow_mut() += 1);
+        drop(subscription);
+
+        let generation = monitor.state.borrow_mut().next_generation();
+        monitor.settle(generation, status(false, ConnectionType::None));
+        assert_eq!(reported.borrow().len(), 1);
+        assert_eq!(*other.borrow(), 0);
+    }
+}
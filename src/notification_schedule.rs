@@ -0,0 +1,469 @@
+use std::fmt::Display;
+
+use crate::local_notifications::{Schedule, ScheduleEvery, ScheduleOn};
+use crate::timestamp::{civil_from_days, days_from_civil, days_in_month, CivilDateTime, Timestamp};
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+/// Every combination of date and weekday repeats within a 400 year cycle.
+const SEARCH_DAYS: i64 = 400 * 366;
+
+/// The reason a schedule is invalid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// A field of `ScheduleOn` is outside its allowed range.
+    OutOfRange {
+        field: &'static str,
+        value: u32,
+        min: u32,
+        max: u32,
+    },
+    /// The day can never occur in the month, e.g. the 30th of February.
+    DayNotInMonth {
+        day: u32,
+        month: u32,
+        year: Option<u32>,
+    },
+}
+
+impl Display for ScheduleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScheduleError::OutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } => write!(f, "{field} must be between {min} and {max} but was {value}"),
+            ScheduleError::DayNotInMonth {
+                day,
+                month,
+                year: Some(year),
+            } => write!(f, "day {day} does not occur in month {month} of {year}"),
+            ScheduleError::DayNotInMonth {
+                day,
+                month,
+                year: None,
+            } => write!(f, "day {day} never occurs in month {month}"),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+impl ScheduleOn {
+    /// Check that every field is in range and that the date can occur.
+    pub fn validate(&self) -> Result<(), ScheduleError> {
+        fn check(
+            field: &'static str,
+            value: Option<u32>,
+            min: u32,
+            max: u32,
+        ) -> Result<(), ScheduleError> {
+            match value {
+                Some(value) if value < min || value > max => Err(ScheduleError::OutOfRange {
+                    field,
+                    value,
+                    min,
+                    max,
+                }),
+                _ => Ok(()),
+            }
+        }
+
+        check("year", self.year, 1, 9999)?;
+        check("month", self.month, 1, 12)?;
+        check("day", self.day, 1, 31)?;
+        check("hour", self.hour, 0, 23)?;
+        check("minute", self.minute, 0, 59)?;
+        check("second", self.second, 0, 59)?;
+
+        if let (Some(day), Some(month)) = (self.day, self.month) {
+            // Use a leap year unless the year is known
+            let max_day = days_in_month(self.year.map(|y| y as i32).unwrap_or(2000), month);
+            if day > max_day {
+                return Err(ScheduleError::DayNotInMonth {
+                    day,
+                    month,
+                    year: self.year,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The fields to match, with unspecified fields finer than the finest specified field set to their minimum.
+    /// Unspecified fields coarser than that match any value.
+    fn pattern(&self) -> Pattern {
+        let levels = [
+            self.year.is_some(),
+            self.month.is_some(),
+            self.day.is_some() || self.weekday.is_some(),
+            self.hour.is_some(),
+            self.minute.is_some(),
+            self.second.is_some(),
+        ];
+        let finest = levels.iter().rposition(|x| *x);
+        let default = |level: usize, min: u32| match finest {
+            Some(finest) if level > finest => Some(min),
+            _ => None,
+        };
+
+        Pattern {
+            year: self.year.map(|y| y as i64),
+            month: self.month.or(default(1, 1)),
+            day: self.day.or(if self.weekday.is_some() {
+                None
+            } else {
+                default(2, 1)
+            }),
+            weekday: self.weekday.clone().map(|w| w as u32),
+            hour: self.hour.or(default(3, 0)),
+            minute: self.minute.or(default(4, 0)),
+            second: self.second.or(default(5, 0)),
+        }
+    }
+}
+
+struct Pattern {
+    year: Option<i64>,
+    month: Option<u32>,
+    day: Option<u32>,
+    weekday: Option<u32>,
+    hour: Option<u32>,
+    minute: Option<u32>,
+    second: Option<u32>,
+}
+
+fn values(value: Option<u32>, max: u32) -> Vec<u32> {
+    match value {
+        Some(value) => vec![value],
+        None => (0..=max).collect(),
+    }
+}
+
+impl Schedule {
+    /// Check that the schedule can be used.
+    pub fn validate(&self) -> Result<(), ScheduleError> {
+        match self {
+            Schedule::On { on, .. } => on.validate(),
+            Schedule::At { .. } | Schedule::Every { .. } => Ok(()),
+        }
+    }
+
+    /// The first `n` times after `from` at which this schedule will fire, interpreting dates and times in UTC.
+    ///
+    /// - `On` fires at every time matching its fields. Unspecified fields finer than the finest specified field are treated as their minimum
+    ///   (e.g. `hour: 9` fires at 09:00:00 every day), and unspecified coarser fields match anything.
+    /// - `Every` fires at each interval after `from`, at most `count` times. A `count` of zero is unlimited.
+    /// - `At` fires at `at`. Only that first time is returned even if it `repeats`, as the interval it repeats with depends on when it was scheduled.
+    ///
+    /// `On` only searches the 400 years after `from`, so a `year` further ahead than that has no occurrences.
+    /// Times which would overflow a `Timestamp` are not returned.
+    pub fn next_occurrences(
+        &self,
+        from: Timestamp,
+        n: usize,
+    ) -> Result<Vec<Timestamp>, ScheduleError> {
+        self.next_occurrences_with_offset(from, n, 0)
+    }
+
+    /// The first `n` times after `from` at which this schedule will fire,
+    /// interpreting dates and times in a time zone `offset_minutes` ahead of UTC.
+    pub fn next_occurrences_with_offset(
+        &self,
+        from: Timestamp,
+        n: usize,
+        offset_minutes: i32,
+    ) -> Result<Vec<Timestamp>, ScheduleError> {
+        self.validate()?;
+        if n == 0 {
+            return Ok(vec![]);
+        }
+
+        let offset = offset_minutes as i64 * 60_000;
+        let local_from = from.as_millis().saturating_add(offset);
+        let to_utc = |local: i64| local.checked_sub(offset).map(Timestamp::from_millis);
+
+        let occurrences = match self {
+            Schedule::On { on, .. } => on_occurrences(on, local_from, n)
+                .into_iter()
+                .map_while(to_utc)
+                .collect(),
+            Schedule::Every { every, count, .. } => {
+                let n = if *count == 0 { n } else { n.min(*count) };
+                (1..=n as i64)
+                    .map_while(|i| add_interval(local_from, every, i).and_then(to_utc))
+                    .collect()
+            }
+            Schedule::At { at, .. } => {
+                if *at > from && n > 0 {
+                    vec![*at]
+                } else {
+                    vec![]
+                }
+            }
+        };
+        Ok(occurrences)
+    }
+}
+
+fn on_occurrences(on: &ScheduleOn, from: i64, n: usize) -> Vec<i64> {
+    let pattern = on.pattern();
+    let hours = values(pattern.hour, 23);
+    let minutes = values(pattern.minute, 59);
+    let seconds = values(pattern.second, 59);
+
+    let from_day = from.div_euclid(MILLIS_PER_DAY);
+    let (mut first_day, mut last_day) = (from_day, from_day + SEARCH_DAYS);
+    if let Some(year) = pattern.year {
+        first_day = first_day.max(days_from_civil(year, 1, 1));
+        last_day = last_day.min(days_from_civil(year, 12, 31));
+    }
+
+    let mut results = vec![];
+    for day in first_day..=last_day {
+        let (_, month, day_of_month) = civil_from_days(day);
+        let weekday = ((day + 4).rem_euclid(7) + 1) as u32;
+        if pattern.month.is_some_and(|m| m != month)
+            || pattern.day.is_some_and(|d| d != day_of_month)
+            || pattern.weekday.is_some_and(|w| w != weekday)
+        {
+            continue;
+        }
+
+        for hour in hours.iter() {
+            for minute in minutes.iter() {
+                for second in seconds.iter() {
+                    let time_of_day =
+                        ((*hour as i64 * 60 + *minute as i64) * 60 + *second as i64) * 1000;
+                    // Later times would overflow too
+                    let Some(time) = day
+                        .checked_mul(MILLIS_PER_DAY)
+                        .and_then(|x| x.checked_add(time_of_day))
+                    else {
+                        return results;
+                    };
+                    if time > from {
+                        results.push(time);
+                        if results.len() == n {
+                            return results;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    results
+}
+
+/// Add `i` intervals to a time. Months and years keep the day of the month where possible, or use the last day of the month.
+fn add_interval(from: i64, every: &ScheduleEvery, i: i64) -> Option<i64> {
+    let fixed = |millis: i64| from.checked_add(millis.checked_mul(i)?);
+    match every {
+        ScheduleEvery::Second => fixed(1000),
+        ScheduleEvery::Minute => fixed(60_000),
+        ScheduleEvery::Hour => fixed(3_600_000),
+        ScheduleEvery::Day => fixed(MILLIS_PER_DAY),
+        ScheduleEvery::Week => fixed(7 * MILLIS_PER_DAY),
+        ScheduleEvery::TwoWeeks => fixed(14 * MILLIS_PER_DAY),
+        ScheduleEvery::Month => add_months(from, i),
+        ScheduleEvery::Year => add_months(from, i.checked_mul(12)?),
+    }
+}
+
+fn add_months(from: i64, months: i64) -> Option<i64> {
+    let civil = Timestamp::from_millis(from).to_civil();
+    let total = (civil.year as i64 * 12 + civil.month as i64 - 1).checked_add(months)?;
+    let year = i32::try_from(total.div_euclid(12)).ok()?;
+    let month = total.rem_euclid(12) as u32 + 1;
+    Timestamp::from_civil(CivilDateTime {
+        year,
+        month,
+        day: civil.day.min(days_in_month(year, month)),
+        ..civil
+    })
+    .map(|t| t.as_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_notifications::Weekday;
+
+    fn ts(s: &str) -> Timestamp {
+        s.parse().unwrap()
+    }
+
+    fn strings(occurrences: Vec<Timestamp>) -> Vec<String> {
+        occurrences.iter().map(|x| x.to_string()).collect()
+    }
+
+    fn on(on: ScheduleOn) -> Schedule {
+        Schedule::from(on)
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(on(ScheduleOn::builder().hour(23).minute(59).build())
+            .validate()
+            .is_ok());
+        assert_eq!(
+            on(ScheduleOn::builder().hour(24).build())
+                .validate()
+                .unwrap_err()
+                .to_string(),
+            "hour must be between 0 and 23 but was 24"
+        );
+        assert_eq!(
+            on(ScheduleOn::builder().month(2).day(30).build())
+                .validate()
+                .unwrap_err()
+                .to_string(),
+            "day 30 never occurs in month 2"
+        );
+        assert_eq!(
+            on(ScheduleOn::builder().year(2023).month(2).day(29).build())
+                .validate()
+                .unwrap_err()
+                .to_string(),
+            "day 29 does not occur in month 2 of 2023"
+        );
+        assert!(on(ScheduleOn::builder().month(2).day(29).build())
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_on() {
+        let from = ts("2024-03-01T09:30:00Z");
+
+        // Finer fields default to their minimum
+        assert_eq!(
+            strings(
+                on(ScheduleOn::builder().hour(9).build())
+                    .next_occurrences(from, 2)
+                    .unwrap()
+            ),
+            ["2024-03-02T09:00:00.000Z", "2024-03-03T09:00:00.000Z"]
+        );
+        // Coarser fields are wildcards
+        assert_eq!(
+            strings(
+                on(ScheduleOn::builder().minute(15).build())
+                    .next_occurrences(from, 2)
+                    .unwrap()
+            ),
+            ["2024-03-01T10:15:00.000Z", "2024-03-01T11:15:00.000Z"]
+        );
+        // 2024-03-01 is a Friday
+        assert_eq!(
+            strings(
+                on(ScheduleOn::builder()
+                    .weekday(Weekday::Monday)
+                    .hour(8)
+                    .build())
+                .next_occurrences(from, 2)
+                .unwrap()
+            ),
+            ["2024-03-04T08:00:00.000Z", "2024-03-11T08:00:00.000Z"]
+        );
+        assert_eq!(
+            strings(
+                on(ScheduleOn::builder().month(2).day(29).build())
+                    .next_occurrences(from, 2)
+                    .unwrap()
+            ),
+            ["2028-02-29T00:00:00.000Z", "2032-02-29T00:00:00.000Z"]
+        );
+        assert_eq!(
+            on(ScheduleOn::builder().year(2023).build())
+                .next_occurrences(from, 2)
+                .unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            strings(
+                on(ScheduleOn::builder().hour(9).build())
+                    .next_occurrences_with_offset(from, 1, 60)
+                    .unwrap()
+            ),
+            ["2024-03-02T08:00:00.000Z"]
+        );
+    }
+
+    #[test]
+    fn test_every_and_at() {
+        let from = ts("2024-01-31T12:00:00Z");
+
+        assert_eq!(
+            strings(
+                Schedule::from((ScheduleEvery::Month, 3))
+                    .next_occurrences(from, 5)
+                    .unwrap()
+            ),
+            [
+                "2024-02-29T12:00:00.000Z",
+                "2024-03-31T12:00:00.000Z",
+                "2024-04-30T12:00:00.000Z"
+            ]
+        );
+        assert_eq!(
+            strings(
+                Schedule::from((ScheduleEvery::Hour, 0))
+                    .next_occurrences(from, 2)
+                    .unwrap()
+            ),
+            ["2024-01-31T13:00:00.000Z", "2024-01-31T14:00:00.000Z"]
+        );
+
+        let at = |repeats| Schedule::At {
+            at: ts("2024-01-31T12:30:00Z"),
+            repeats,
+            allow_while_idle: false,
+        };
+        assert_eq!(
+            strings(at(false).next_occurrences(from, 3).unwrap()),
+            ["2024-01-31T12:30:00.000Z"]
+        );
+        assert_eq!(
+            strings(at(true).next_occurrences(from, 3).unwrap()),
+            ["2024-01-31T12:30:00.000Z"]
+        );
+        assert_eq!(at(true).next_occurrences(from, 0).unwrap(), vec![]);
+        assert_eq!(
+            at(true)
+                .next_occurrences(ts("2024-02-01T00:00:00Z"), 3)
+                .unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let near_max = Timestamp::from_millis(i64::MAX - 500);
+        let daily = on(ScheduleOn::builder().hour(9).build());
+        assert!(daily.next_occurrences(near_max, 3).unwrap().is_empty());
+        assert!(daily
+            .next_occurrences_with_offset(near_max, 3, -60)
+            .unwrap()
+            .is_empty());
+
+        let every = Schedule::Every {
+            every: ScheduleEvery::Second,
+            count: 0,
+            allow_while_idle: false,
+        };
+        assert!(every.next_occurrences(near_max, 3).unwrap().is_empty());
+
+        let at = Schedule::At {
+            at: Timestamp::from_millis(i64::MAX),
+            repeats: true,
+            allow_while_idle: false,
+        };
+        assert_eq!(
+            at.next_occurrences(Timestamp::from_millis(i64::MIN), 3)
+                .unwrap(),
+            [Timestamp::from_millis(i64::MAX)]
+        );
+    }
+}
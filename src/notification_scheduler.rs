@@ -0,0 +1,373 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    ops::RangeInclusive,
+    rc::Rc,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::local_notifications::{
+    CancelOptions, LocalNotificationDescriptor, LocalNotificationSchema, LocalNotifications,
+    Schedule,
+};
+use crate::preferences::{to_json, Preferences};
+
+/// A notification which was scheduled for a logical key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledEntry {
+    /// The notification id allocated for the key.
+    pub id: i32,
+    /// A hash of the notification content, used to detect changes.
+    pub content_hash: u64,
+    /// The notification `group` or, failing that, `thread_identifier`.
+    pub group: Option<String>,
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SchedulerState {
+    next_id: Option<i32>,
+    entries: BTreeMap<String, ScheduledEntry>,
+}
+
+impl SchedulerState {
+    /// Allocate the next unused id in the range, wrapping around at the end.
+    fn allocate_id(&mut self, range: &RangeInclusive<i32>) -> Result<i32, Error> {
+        let used: BTreeSet<i32> = self.entries.values().map(|x| x.id).collect();
+        let start = self
+            .next_id
+            .filter(|id| range.contains(id))
+            .unwrap_or(*range.start());
+
+        let id = (start..=*range.end())
+            .chain(*range.start()..start)
+            .find(|id| !used.contains(id))
+            .ok_or(Error::NotificationIdsExhausted {
+                start: *range.start(),
+                end: *range.end(),
+            })?;
+
+        self.next_id = id.checked_add(1);
+        Ok(id)
+    }
+}
+
+/// What `NotificationScheduler::schedule` did.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScheduleOutcome {
+    /// The key had no notification, so a new one was scheduled.
+    Scheduled { id: i32 },
+    /// The content had changed, so the old notification was cancelled and the new one scheduled.
+    Rescheduled { id: i32 },
+    /// An identical notification is already pending, or has already been delivered and does not repeat, so nothing was done.
+    Unchanged { id: i32 },
+}
+
+impl ScheduleOutcome {
+    pub fn id(&self) -> i32 {
+        match self {
+            ScheduleOutcome::Scheduled { id }
+            | ScheduleOutcome::Rescheduled { id }
+            | ScheduleOutcome::Unchanged { id } => *id,
+        }
+    }
+}
+
+/// The changes made by `NotificationScheduler::reconcile`.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct ReconcileReport {
+    /// Keys whose notifications have been delivered. These are no longer tracked.
+    pub delivered_keys: Vec<String>,
+    /// Keys whose notifications are neither pending nor delivered, e.g. because they were cancelled elsewhere. These are no longer tracked.
+    pub missing_keys: Vec<String>,
+    /// Pending notifications in the scheduler's id range which no key refers to. These have been cancelled.
+    pub cancelled_orphans: Vec<i32>,
+}
+
+/// Schedules local notifications by logical key (e.g. `"reminder:42"`) rather than by id.
+///
+/// Ids are allocated from a range reserved for the scheduler, so they cannot collide with notifications scheduled elsewhere.
+/// The mapping from keys to ids is stored in preferences.
+#[derive(Clone, Debug)]
+pub struct NotificationScheduler {
+    storage_key: String,
+    ids: RangeInclusive<i32>,
+    state: Rc<RefCell<SchedulerState>>,
+}
+
+impl NotificationScheduler {
+    /// Load the mapping stored in preferences under the given key.
+    /// Only ids in the given range will be used, and `reconcile` will cancel untracked notifications in it.
+    /// Use a key starting with `RESERVED_KEY_PREFIX` so the mapping is left out of `Preferences::export`.
+    pub async fn load(
+        storage_key: impl Into<String>,
+        ids: RangeInclusive<i32>,
+    ) -> Result<Self, Error> {
+        let storage_key = storage_key.into();
+        let state: SchedulerState = Preferences::get_json(&storage_key)
+            .await?
+            .unwrap_or_default();
+        Ok(Self {
+            storage_key,
+            ids,
+            state: Rc::new(RefCell::new(state)),
+        })
+    }
+
+    async fn save(&self) -> Result<(), Error> {
+        let state = self.state.borrow().clone();
+        Preferences::set_json(&self.storage_key, &state).await
+    }
+
+    /// The notification tracked for a key, if any.
+    pub fn entry(&self, key: &str) -> Option<ScheduledEntry> {
+        self.state.borrow().entries.get(key).cloned()
+    }
+
+    /// Every tracked key and its notification.
+    pub fn entries(&self) -> BTreeMap<String, ScheduledEntry> {
+        self.state.borrow().entries.clone()
+    }
+
+    /// Schedule a notification for a key. Its id will be replaced with the key's id.
+    ///
+    /// If an identical notification is already pending for the key, or has already been delivered and does not repeat, nothing is done.
+    /// If a different one is pending, it is cancelled first.
+    pub async fn schedule(
+        &self,
+        key: &str,
+        mut notification: LocalNotificationSchema,
+    ) -> Result<ScheduleOutcome, Error> {
+        let existing = self.entry(key);
+        let id = match &existing {
+            Some(entry) => entry.id,
+            None => self.state.borrow_mut().allocate_id(&self.ids)?,
+        };
+        notification.id = id;
+        let content_hash = content_hash(&notification)?;
+
+        if let Some(entry) = &existing {
+            if entry.content_hash == content_hash {
+                let pending = LocalNotifications::get_pending().await?;
+                if pending.notifications.iter().any(|x| x.id == id) {
+                    return Ok(ScheduleOutcome::Unchanged { id });
+                }
+                if is_one_shot(&notification) {
+                    let delivered = LocalNotifications::get_delivered_notifications().await?;
+                    if delivered.notifications.iter().any(|x| x.id == id) {
+                        return Ok(ScheduleOutcome::Unchanged { id });
+                    }
+                }
+            }
+            LocalNotifications::cancel(descriptors([id])).await?;
+        }
+
+        let group = notification
+            .group
+            .clone()
+            .or_else(|| notification.thread_identifier.clone());
+        LocalNotifications::schedule(notification).await?;
+
+        self.state.borrow_mut().entries.insert(
+            key.to_string(),
+            ScheduledEntry {
+                id,
+                content_hash,
+                group,
+            },
+        );
+        self.save().await?;
+
+        Ok(match existing {
+            Some(_) => ScheduleOutcome::Rescheduled { id },
+            None => ScheduleOutcome::Scheduled { id },
+        })
+    }
+
+    /// Cancel the notification for a key and stop tracking it.
+    /// Returns whether the key was tracked.
+    pub async fn cancel_by_key(&self, key: &str) -> Result<bool, Error> {
+        let Some(entry) = self.entry(key) else {
+            return Ok(false);
+        };
+        LocalNotifications::cancel(descriptors([entry.id])).await?;
+        self.state.borrow_mut().entries.remove(key);
+        self.save().await?;
+        Ok(true)
+    }
+
+    /// Cancel every tracked notification in a group and stop tracking them.
+    /// Returns the keys which were cancelled.
+    pub async fn cancel_group(&self, group: &str) -> Result<Vec<String>, Error> {
+        let (keys, ids): (Vec<String>, Vec<i32>) = self
+            .state
+            .borrow()
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.group.as_deref() == Some(group))
+            .map(|(key, entry)| (key.clone(), entry.id))
+            .unzip();
+        if keys.is_empty() {
+            return Ok(keys);
+        }
+
+        LocalNotifications::cancel(descriptors(ids)).await?;
+        {
+            let mut state = self.state.borrow_mut();
+            for key in keys.iter() {
+                state.entries.remove(key);
+            }
+        }
+        self.save().await?;
+        Ok(keys)
+    }
+
+    /// Bring the tracked keys in line with the notifications the OS actually has, e.g. on startup.
+    ///
+    /// Keys whose notifications are no longer pending stop being tracked,
+    /// and pending notifications in the scheduler's id range which no key refers to are cancelled.
+    pub async fn reconcile(&self) -> Result<ReconcileReport, Error> {
+        let pending: BTreeSet<i32> = LocalNotifications::get_pending()
+            .await?
+            .notifications
+            .iter()
+            .map(|x| x.id)
+            .collect();
+        let delivered: BTreeSet<i32> = LocalNotifications::get_delivered_notifications()
+            .await?
+            .notifications
+            .iter()
+            .map(|x| x.id)
+            .collect();
+
+        let mut report = ReconcileReport::default();
+        let tracked: BTreeSet<i32> = {
+            let mut state = self.state.borrow_mut();
+            state.entries.retain(|key, entry| {
+                if pending.contains(&entry.id) {
+                    return true;
+                }
+                if delivered.contains(&entry.id) {
+                    report.delivered_keys.push(key.clone());
+                } else {
+                    report.missing_keys.push(key.clone());
+                }
+                false
+            });
+            state.entries.values().map(|x| x.id).collect()
+        };
+
+        report.cancelled_orphans = pending
+            .iter()
+            .filter(|id| self.ids.contains(id) && !tracked.contains(id))
+            .copied()
+            .collect();
+        if !report.cancelled_orphans.is_empty() {
+            LocalNotifications::cancel(descriptors(report.cancelled_orphans.clone())).await?;
+        }
+
+        self.save().await?;
+        Ok(report)
+    }
+}
+
+fn descriptors(ids: impl IntoIterator<Item = i32>) -> CancelOptions {
+    CancelOptions {
+        notifications: ids
+            .into_iter()
+            .map(|id| LocalNotificationDescriptor { id })
+            .collect(),
+    }
+}
+
+/// Whether the notification is delivered only once, so delivering it again would be a duplicate.
+fn is_one_shot(notification: &LocalNotificationSchema) -> bool {
+    matches!(notification.schedule, Schedule::At { repeats: false, .. })
+}
+
+/// A stable FNV-1a hash of the notification's json.
+fn content_hash(notification: &LocalNotificationSchema) -> Result<u64, Error> {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let json = to_json(notification)?;
+    Ok(json.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_notifications::ScheduleOn;
+    use crate::timestamp::Timestamp;
+
+    fn entry(id: i32) -> ScheduledEntry {
+        ScheduledEntry {
+            id,
+            content_hash: 0,
+            group: None,
+        }
+    }
+
+    #[test]
+    fn test_allocate_id() {
+        let range = 100..=103;
+        let mut state = SchedulerState::default();
+        assert_eq!(state.allocate_id(&range).unwrap(), 100);
+        state.entries.insert("a".to_string(), entry(100));
+        assert_eq!(state.allocate_id(&range).unwrap(), 101);
+        state.entries.insert("c".to_string(), entry(103));
+        assert_eq!(state.allocate_id(&range).unwrap(), 102);
+        state.entries.insert("b".to_string(), entry(102));
+        // Wraps around, skipping ids in use
+        assert_eq!(state.allocate_id(&range).unwrap(), 101);
+        state.entries.insert("d".to_string(), entry(101));
+        assert!(state.allocate_id(&range).is_err());
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let notification = LocalNotificationSchema::builder()
+            .title("Title")
+            .body("Body")
+            .schedule(ScheduleOn::builder().hour(9).build())
+            .id(1)
+            .auto_cancel(true)
+            .build();
+        let changed = LocalNotificationSchema {
+            body: "Other body".to_string(),
+            ..notification.clone()
+        };
+
+        let hash = content_hash(&notification).unwrap();
+        assert_eq!(hash, content_hash(&notification.clone()).unwrap());
+        assert_ne!(hash, content_hash(&changed).unwrap());
+    }
+
+    #[test]
+    fn test_is_one_shot() {
+        let notification = LocalNotificationSchema::builder()
+            .title("Title")
+            .body("Body")
+            .schedule(ScheduleOn::builder().hour(9).build())
+            .id(1)
+            .auto_cancel(true)
+            .build();
+        assert!(!is_one_shot(&notification));
+
+        let at = |repeats| LocalNotificationSchema {
+            schedule: Schedule::At {
+                at: Timestamp::from_millis(0),
+                repeats,
+                allow_while_idle: false,
+            },
+            ..notification.clone()
+        };
+        assert!(is_one_shot(&at(false)));
+        assert!(!is_one_shot(&at(true)));
+    }
+}
@@ -0,0 +1,478 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::helpers::sleep;
+use crate::network::{ConnectionStatus, ConnectionType, Network};
+use crate::plugin_listener_handle::PluginListenerHandle;
+use crate::preferences::Preferences;
+
+/// A unit of work which needs the network, stored until it can be performed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkItem {
+    /// A unique identifier for this item.
+    pub id: u64,
+    /// The name of the handler which will perform this item.
+    pub handler: String,
+    /// The data passed to the handler.
+    pub payload: serde_json::Value,
+    /// Which connections this item may be performed on.
+    #[serde(default)]
+    pub requirement: NetworkRequirement,
+    /// The number of failed attempts so far.
+    #[serde(default)]
+    pub attempts: u32,
+}
+
+/// Which connections a work item may be performed on.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkRequirement {
+    /// Any active connection.
+    #[default]
+    Any,
+    /// Only when connected via wifi e.g. for large uploads.
+    Wifi,
+}
+
+impl NetworkRequirement {
+    /// Whether this requirement is met by the given connection.
+    pub fn is_met_by(&self, status: &ConnectionStatus) -> bool {
+        match self {
+            NetworkRequirement::Any => status.connected,
+            NetworkRequirement::Wifi => {
+                status.connected && status.connection_type == ConnectionType::Wifi
+            }
+        }
+    }
+}
+
+/// How failed work items are retried.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The number of attempts after which an item is abandoned.
+    pub max_attempts: u32,
+    /// The delay after the first failure. This doubles after each subsequent failure.
+    pub initial_delay: Duration,
+    /// The maximum delay between attempts.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before retrying an item which has failed this many times.
+    pub fn delay(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+}
+
+/// The outcome of flushing the queue.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct FlushResult {
+    /// The ids of the items which were performed successfully.
+    pub completed: Vec<u64>,
+    /// The items which were abandoned after too many failures, with the last error message.
+    pub abandoned: Vec<(WorkItem, String)>,
+}
+
+type HandlerFuture = Pin<Box<dyn Future<Output = Result<(), String>>>>;
+type Handler = Rc<dyn Fn(serde_json::Value) -> HandlerFuture>;
+type Policy = Rc<dyn Fn(&WorkItem, &ConnectionStatus) -> bool>;
+type AbandonedCallback = Rc<dyn Fn(&WorkItem, &str)>;
+
+/// A queue of work items which is stored in preferences and performed, in the order they were enqueued, when the network is available.
+/// An item which fails is retried after a delay, and the items behind it wait until it succeeds or is abandoned.
+///
+/// Items are flushed whenever the network reconnects and whenever an item is enqueued while connected.
+/// Handlers must be registered by name before items using them can be performed.
+/// An item with no registered handler, or which the policy does not allow on the current connection, also makes the items behind it wait.
+/// Call `set_in_order(false)` to perform the items behind such an item in the meantime.
+#[derive(Clone)]
+pub struct OfflineQueue {
+    state: Rc<RefCell<QueueState>>,
+}
+
+/// The queue as it is stored in preferences.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct StoredQueue {
+    /// The id of the next item to be enqueued. Ids are never reused.
+    next_id: u64,
+    items: Vec<WorkItem>,
+}
+
+impl StoredQueue {
+    fn allocate_id(&mut self) -> u64 {
+        let id = self
+            .items
+            .iter()
+            .map(|x| x.id + 1)
+            .max()
+            .unwrap_or_default()
+            .max(self.next_id);
+        self.next_id = id + 1;
+        id
+    }
+}
+
+struct QueueState {
+    storage_key: String,
+    stored: StoredQueue,
+    handlers: BTreeMap<String, Handler>,
+    policy: Policy,
+    retry: RetryPolicy,
+    status: ConnectionStatus,
+    in_order: bool,
+    on_abandoned: Option<AbandonedCallback>,
+    flushing: bool,
+    listener: Option<PluginListenerHandle>,
+}
+
+impl Drop for QueueState {
+    fn drop(&mut self) {
+        if let Some(listener) = self.listener.take() {
+            wasm_bindgen_futures::spawn_local(async move {
+                let _ = listener.remove_async().await;
+            });
+        }
+    }
+}
+
+impl std::fmt::Debug for OfflineQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("OfflineQueue")
+            .field("storage_key", &state.storage_key)
+            .field("items", &state.stored.items)
+            .field("status", &state.status)
+            .finish()
+    }
+}
+
+impl OfflineQueue {
+    /// Load the queue stored in preferences under the given key and start listening for network changes.
+    /// Use a key starting with `RESERVED_KEY_PREFIX` so the queue is left out of `Preferences::export`.
+    pub async fn load(storage_key: impl Into<String>, retry: RetryPolicy) -> Result<Self, Error> {
+        let storage_key = storage_key.into();
+        let stored: StoredQueue = Preferences::get_json(&storage_key)
+            .await?
+            .unwrap_or_default();
+        let status = Network::get_status().await?;
+
+        let state = Rc::new(RefCell::new(QueueState {
+            storage_key,
+            stored,
+            handlers: Default::default(),
+            policy: Rc::new(|item, status| item.requirement.is_met_by(status)),
+            retry,
+            status,
+            in_order: true,
+            on_abandoned: None,
+            flushing: false,
+            listener: None,
+        }));
+
+        let weak = Rc::downgrade(&state);
+        let listener = Network::add_network_change_listener(move |status| {
+            if let Some(queue) = Self::upgrade(&weak) {
+                queue.on_status_changed(status);
+            }
+        })
+        .await?;
+        state.borrow_mut().listener = Some(listener);
+
+        Ok(Self { state })
+    }
+
+    fn upgrade(weak: &Weak<RefCell<QueueState>>) -> Option<Self> {
+        weak.upgrade().map(|state| Self { state })
+    }
+
+    fn on_status_changed(&self, status: ConnectionStatus) {
+        let connected = status.connected;
+        self.state.borrow_mut().status = status;
+        if connected {
+            self.spawn_flush();
+        }
+    }
+
+    fn spawn_flush(&self) {
+        let queue = self.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let _ = queue.flush().await;
+        });
+    }
+
+    /// Register the handler which performs items with the given name.
+    /// The handler should return an error message if the item should be retried.
+    pub fn register_handler<F, Fut>(&self, name: impl Into<String>, handler: F)
+    where
+        F: Fn(serde_json::Value) -> Fut + 'static,
+        Fut: Future<Output = Result<(), String>> + 'static,
+    {
+        let handler: Handler = Rc::new(move |payload| Box::pin(handler(payload)));
+        self.state
+            .borrow_mut()
+            .handlers
+            .insert(name.into(), handler);
+    }
+
+    /// Replace the policy which decides whether an item may be performed on the current connection.
+    /// By default this checks the item's `NetworkRequirement`.
+    pub fn set_policy(&self, policy: impl Fn(&WorkItem, &ConnectionStatus) -> bool + 'static) {
+        self.state.borrow_mut().policy = Rc::new(policy);
+    }
+
+    /// Set whether items must be performed in the order they were enqueued. This is `true` by default.
+    /// If `false`, items which cannot be performed yet, or are waiting to be retried, do not hold up the items behind them.
+    pub fn set_in_order(&self, in_order: bool) {
+        self.state.borrow_mut().in_order = in_order;
+    }
+
+    /// Set a callback which is called with each item which is abandoned after too many failures, and its last error message.
+    /// This is the only way to find out about items abandoned when the queue is flushed automatically.
+    pub fn on_abandoned(&self, callback: impl Fn(&WorkItem, &str) + 'static) {
+        self.state.borrow_mut().on_abandoned = Some(Rc::new(callback));
+    }
+
+    /// The items waiting to be performed.
+    pub fn items(&self) -> Vec<WorkItem> {
+        self.state.borrow().stored.items.clone()
+    }
+
+    /// The most recently reported network status.
+    pub fn status(&self) -> ConnectionStatus {
+        self.state.borrow().status.clone()
+    }
+
+    /// Add an item to the end of the queue and save the queue.
+    /// If the network is connected, the queue will be flushed.
+    /// Returns the id of the new item.
+    pub async fn enqueue<T: Serialize>(
+        &self,
+        handler: impl Into<String>,
+        payload: &T,
+        requirement: NetworkRequirement,
+    ) -> Result<u64, Error> {
+        let payload = serde_json::to_value(payload).map_err(|error| Error::JsonSerializeError {
+            typename: std::any::type_name::<T>(),
+            error,
+        })?;
+
+        let (id, connected) = {
+            let mut state = self.state.borrow_mut();
+            let id = state.stored.allocate_id();
+            state.stored.items.push(WorkItem {
+                id,
+                handler: handler.into(),
+                payload,
+                requirement,
+                attempts: 0,
+            });
+            (id, state.status.connected)
+        };
+        self.save().await?;
+
+        if connected {
+            self.spawn_flush();
+        }
+        Ok(id)
+    }
+
+    /// Remove an item from the queue without performing it.
+    pub async fn cancel(&self, id: u64) -> Result<(), Error> {
+        self.remove_item(id);
+        self.save().await
+    }
+
+    fn remove_item(&self, id: u64) {
+        self.state.borrow_mut().stored.items.retain(|x| x.id != id);
+    }
+
+    async fn save(&self) -> Result<(), Error> {
+        let (key, stored) = {
+            let state = self.state.borrow();
+            (state.storage_key.clone(), state.stored.clone())
+        };
+        Preferences::set_json(&key, &stored).await
+    }
+
+    /// Perform, in order, every item which is allowed on the current connection.
+    /// Failed items are retried after a delay until they succeed or reach the maximum number of attempts.
+    /// Stops at the first item which cannot be performed, unless `set_in_order(false)` has been called.
+    /// Does nothing if the queue is already being flushed.
+    pub async fn flush(&self) -> Result<FlushResult, Error> {
+        {
+            let mut state = self.state.borrow_mut();
+            if state.flushing {
+                return Ok(FlushResult::default());
+            }
+            state.flushing = true;
+        }
+
+        let result = self.flush_items().await;
+        self.state.borrow_mut().flushing = false;
+        result
+    }
+
+    async fn flush_items(&self) -> Result<FlushResult, Error> {
+        let mut result = FlushResult::default();
+        // When each failed item may next be retried, in milliseconds since the unix epoch
+        let mut retry_at: BTreeMap<u64, f64> = BTreeMap::new();
+
+        loop {
+            let now = js_sys::Date::now();
+            let mut earliest_retry: Option<f64> = None;
+            let next = {
+                let state = self.state.borrow();
+                if !state.status.connected {
+                    break;
+                }
+                let mut next = None;
+                for item in state.stored.items.iter() {
+                    let handler = state
+                        .handlers
+                        .get(&item.handler)
+                        .filter(|_| (state.policy)(item, &state.status));
+                    if let Some(handler) = handler {
+                        match retry_at.get(&item.id).filter(|&&at| at > now) {
+                            Some(&at) => {
+                                earliest_retry = Some(earliest_retry.map_or(at, |x: f64| x.min(at)))
+                            }
+                            None => {
+                                next = Some((item.clone(), handler.clone(), state.retry));
+                                break;
+                            }
+                        }
+                    }
+                    // In order, nothing behind the first item may be performed
+                    if state.in_order {
+                        break;
+                    }
+                }
+                next
+            };
+            let Some((item, handler, retry)) = next else {
+                match earliest_retry {
+                    Some(at) => {
+                        sleep(Duration::from_secs_f64((at - now) / 1000.0)).await;
+                        continue;
+                    }
+                    None => break,
+                }
+            };
+
+            match handler(item.payload.clone()).await {
+                Ok(()) => {
+                    self.remove_item(item.id);
+                    result.completed.push(item.id);
+                    self.save().await?;
+                }
+                Err(message) => {
+                    let attempts = item.attempts + 1;
+                    if attempts >= retry.max_attempts {
+                        self.remove_item(item.id);
+                        let item = WorkItem { attempts, ..item };
+                        let on_abandoned = self.state.borrow().on_abandoned.clone();
+                        if let Some(on_abandoned) = on_abandoned {
+                            on_abandoned(&item, &message);
+                        }
+                        result.abandoned.push((item, message));
+                        self.save().await?;
+                    } else {
+                        let found = self
+                            .state
+                            .borrow_mut()
+                            .stored
+                            .items
+                            .iter_mut()
+                            .find(|x| x.id == item.id)
+                            .map(|x| x.attempts = attempts)
+                            .is_some();
+                        // Items cancelled while they were being performed are not retried
+                        if found {
+                            let delay = retry.delay(attempts).as_secs_f64() * 1000.0;
+                            retry_at.insert(item.id, js_sys::Date::now() + delay);
+                            self.save().await?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay() {
+        let retry = RetryPolicy::default();
+        assert_eq!(retry.delay(1), Duration::from_secs(1));
+        assert_eq!(retry.delay(2), Duration::from_secs(2));
+        assert_eq!(retry.delay(4), Duration::from_secs(8));
+        assert_eq!(retry.delay(10), Duration::from_secs(60));
+        assert_eq!(retry.delay(100), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_allocate_id() {
+        let mut stored = StoredQueue::default();
+        assert_eq!(stored.allocate_id(), 0);
+        assert_eq!(stored.allocate_id(), 1);
+
+        // Ids are not reused once the queue has drained
+        stored.items.clear();
+        assert_eq!(stored.allocate_id(), 2);
+
+        // Stored items with higher ids are respected
+        let mut stored: StoredQueue =
+            serde_json::from_str(r#"{"items":[{"id":7,"handler":"upload","payload":null}]}"#)
+                .unwrap();
+        assert_eq!(stored.allocate_id(), 8);
+    }
+
+    #[test]
+    fn test_requirement() {
+        let wifi = ConnectionStatus {
+            connected: true,
+            connection_type: ConnectionType::Wifi,
+        };
+        let cellular = ConnectionStatus {
+            connected: true,
+            connection_type: ConnectionType::Cellular,
+        };
+        assert!(NetworkRequirement::Any.is_met_by(&cellular));
+        assert!(NetworkRequirement::Wifi.is_met_by(&wifi));
+        assert!(!NetworkRequirement::Wifi.is_met_by(&cellular));
+        let offline = ConnectionStatus {
+            connected: false,
+            connection_type: ConnectionType::None,
+        };
+        assert!(!NetworkRequirement::Any.is_met_by(&offline));
+    }
+}
@@ -0,0 +1,362 @@
+use std::cell::RefCell;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::Error;
+use crate::preferences::{GetOptions, Preferences, RemoveOptions, SetOptions};
+
+/// The prefix of preferences keys reserved for internal state, which are left out of `Preferences::export`.
+pub const RESERVED_KEY_PREFIX: &str = "__";
+
+/// The reserved preferences key in which the current schema version is stored.
+pub const SCHEMA_VERSION_KEY: &str = "__schema_version";
+
+/// Whether a preferences key is reserved for internal state.
+pub fn is_reserved_key(key: &str) -> bool {
+    key.starts_with(RESERVED_KEY_PREFIX)
+}
+
+thread_local! {
+    /// The schema version most recently read or written, and the preferences group it belongs to.
+    static CACHED_SCHEMA_VERSION: RefCell<Option<(String, u32)>> = const { RefCell::new(None) };
+}
+
+fn cached_schema_version() -> Option<u32> {
+    let group = Preferences::configured_group();
+    CACHED_SCHEMA_VERSION.with(|x| {
+        x.borrow()
+            .as_ref()
+            .filter(|(cached_group, _)| *cached_group == group)
+            .map(|(_, version)| *version)
+    })
+}
+
+fn cache_schema_version(version: u32) {
+    let group = Preferences::configured_group();
+    CACHED_SCHEMA_VERSION.with(|x| *x.borrow_mut() = Some((group, version)));
+}
+
+/// Forget the cached schema version if the given key is being changed directly.
+pub(crate) fn invalidate_schema_version(key: &str) {
+    if key == SCHEMA_VERSION_KEY {
+        CACHED_SCHEMA_VERSION.with(|x| *x.borrow_mut() = None);
+    }
+}
+
+/// A typed key for a value stored as json in preferences.
+///
+/// A key with a version greater than zero can only be read or written once the stored schema version has reached that version,
+/// so values are never read or written in a shape which a pending migration would change.
+/// Use version zero for keys which do not take part in migrations.
+///
+/// The schema version only advances when a registered migration reaches it, so a key with version `v` needs a migration with version `v` to be run first,
+/// even if nothing needs to change: register a no-op `Migration::new(v)` in that case.
+/// ```ignore
+/// const VOLUME: PrefKey<f64> = PrefKey::new("volume", 1, || 0.5);
+/// const MUTED: PrefKey<bool> = PrefKey::new("muted", 2, || false);
+///
+/// Migrations::new()
+///     .with(Migration::new(1).rename("old_volume", "volume"))
+///     .with(Migration::new(2)) // `muted` is new, so nothing needs migrating
+///     .run()
+///     .await?;
+///
+/// let volume = Preferences::get_key(&VOLUME).await?;
+/// Preferences::set_key(&VOLUME, &(volume / 2.0)).await?;
+/// ```
+pub struct PrefKey<T> {
+    /// The name of the key in preferences.
+    pub name: &'static str,
+    /// The schema version in which this key was introduced or last changed shape, or zero if it does not take part in migrations.
+    pub version: u32,
+    default: fn() -> T,
+}
+
+impl<T> PrefKey<T> {
+    pub const fn new(name: &'static str, version: u32, default: fn() -> T) -> Self {
+        Self {
+            name,
+            version,
+            default,
+        }
+    }
+
+    /// The value to use when nothing is stored for this key.
+    pub fn default_value(&self) -> T {
+        (self.default)()
+    }
+
+    /// Check that the stored schema version is new enough for this key.
+    pub(crate) fn check_schema_version(&self, schema_version: u32) -> Result<(), Error> {
+        if schema_version < self.version {
+            return Err(Error::PreferenceSchemaOutdated {
+                key: self.name.to_string(),
+                version: self.version,
+                schema_version,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<T> Clone for PrefKey<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for PrefKey<T> {}
+
+impl<T> std::fmt::Debug for PrefKey<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrefKey")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .finish()
+    }
+}
+
+impl Preferences {
+    /// Get the value for a typed key, or its default value if nothing is stored.
+    /// Returns an error if the stored schema version is older than the key's version.
+    pub async fn get_key<T: DeserializeOwned>(key: &PrefKey<T>) -> Result<T, Error> {
+        Self::check_schema_version(key).await?;
+        let value = Self::get_json(key.name).await?;
+        Ok(value.unwrap_or_else(|| key.default_value()))
+    }
+
+    /// Set the value for a typed key.
+    /// Returns an error if the stored schema version is older than the key's version.
+    pub async fn set_key<T: Serialize>(key: &PrefKey<T>, value: &T) -> Result<(), Error> {
+        Self::check_schema_version(key).await?;
+        Self::set_json(key.name, value).await
+    }
+
+    /// Remove the value for a typed key, so it will return to its default value.
+    pub async fn remove_key<T>(key: &PrefKey<T>) -> Result<(), Error> {
+        Self::remove(RemoveOptions::from(key.name)).await
+    }
+
+    /// Get the schema version stored in preferences. This is zero if no migrations have been run.
+    /// The version is cached in memory after it has been read or written by this crate.
+    pub async fn schema_version() -> Result<u32, Error> {
+        if let Some(version) = cached_schema_version() {
+            return Ok(version);
+        }
+        let version = Self::get_json(SCHEMA_VERSION_KEY)
+            .await?
+            .unwrap_or_default();
+        cache_schema_version(version);
+        Ok(version)
+    }
+
+    async fn check_schema_version<T>(key: &PrefKey<T>) -> Result<(), Error> {
+        if key.version == 0 {
+            return Ok(());
+        }
+        key.check_schema_version(Self::schema_version().await?)
+    }
+}
+
+/// A change to the stored preferences made by a migration.
+pub enum MigrationStep {
+    /// Move the value of one key to another, replacing any existing value.
+    Rename { from: String, to: String },
+    /// Remove the value of a key.
+    Remove { key: String },
+    /// Change the shape of the value of a key.
+    /// Values which are not valid json are passed to the transform as json strings.
+    Transform {
+        key: String,
+        transform: Box<dyn Fn(serde_json::Value) -> serde_json::Value>,
+    },
+}
+
+impl std::fmt::Debug for MigrationStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rename { from, to } => f
+                .debug_struct("Rename")
+                .field("from", from)
+                .field("to", to)
+                .finish(),
+            Self::Remove { key } => f.debug_struct("Remove").field("key", key).finish(),
+            Self::Transform { key, .. } => f.debug_struct("Transform").field("key", key).finish(),
+        }
+    }
+}
+
+/// The steps needed to upgrade stored preferences to a particular schema version.
+#[derive(Debug)]
+pub struct Migration {
+    /// The schema version after this migration has run.
+    pub version: u32,
+    pub steps: Vec<MigrationStep>,
+}
+
+impl Migration {
+    pub fn new(version: u32) -> Self {
+        Self {
+            version,
+            steps: vec![],
+        }
+    }
+
+    /// Move the value of one key to another.
+    pub fn rename(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.steps.push(MigrationStep::Rename {
+            from: from.into(),
+            to: to.into(),
+        });
+        self
+    }
+
+    /// Remove the value of a key.
+    pub fn remove(mut self, key: impl Into<String>) -> Self {
+        self.steps.push(MigrationStep::Remove { key: key.into() });
+        self
+    }
+
+    /// Change the shape of the value of a key.
+    pub fn transform(
+        mut self,
+        key: impl Into<String>,
+        transform: impl Fn(serde_json::Value) -> serde_json::Value + 'static,
+    ) -> Self {
+        self.steps.push(MigrationStep::Transform {
+            key: key.into(),
+            transform: Box::new(transform),
+        });
+        self
+    }
+
+    async fn apply(&self) -> Result<(), Error> {
+        for step in self.steps.iter() {
+            match step {
+                MigrationStep::Rename { from, to } => {
+                    if let Some(value) = Preferences::get(GetOptions::from(from.as_str()))
+                        .await?
+                        .value
+                    {
+                        Preferences::set(SetOptions {
+                            key: to.clone(),
+                            value,
+                        })
+                        .await?;
+                        Preferences::remove(RemoveOptions::from(from.as_str())).await?;
+                    }
+                }
+                MigrationStep::Remove { key } => {
+                    Preferences::remove(RemoveOptions::from(key.as_str())).await?;
+                }
+                MigrationStep::Transform { key, transform } => {
+                    if let Some(value) = Preferences::get(GetOptions::from(key.as_str()))
+                        .await?
+                        .value
+                    {
+                        let json = serde_json::from_str(&value)
+                            .unwrap_or(serde_json::Value::String(value));
+                        Preferences::set_json(key, &transform(json)).await?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A registry of migrations, to be run on startup to upgrade stored preferences from older schema versions.
+/// The current schema version is stored under `SCHEMA_VERSION_KEY`.
+#[derive(Debug, Default)]
+pub struct Migrations {
+    migrations: Vec<Migration>,
+}
+
+impl Migrations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a migration.
+    ///
+    /// # Panics
+    /// Panics if a migration with the same version has already been registered.
+    pub fn with(mut self, migration: Migration) -> Self {
+        match self
+            .migrations
+            .binary_search_by_key(&migration.version, |x| x.version)
+        {
+            Ok(_) => panic!(
+                "A migration with version {} has already been registered",
+                migration.version
+            ),
+            Err(index) => self.migrations.insert(index, migration),
+        }
+        self
+    }
+
+    /// The schema version after all migrations have run.
+    pub fn latest_version(&self) -> u32 {
+        self.migrations
+            .last()
+            .map(|x| x.version)
+            .unwrap_or_default()
+    }
+
+    /// Run, in order, every migration newer than the stored schema version.
+    /// The schema version is saved after each migration so an interrupted run can be resumed.
+    /// Returns the schema version afterwards.
+    pub async fn run(&self) -> Result<u32, Error> {
+        let mut version = Preferences::schema_version().await?;
+        for migration in self.migrations.iter() {
+            if migration.version <= version {
+                continue;
+            }
+            migration.apply().await?;
+            version = migration.version;
+            Preferences::set_json(SCHEMA_VERSION_KEY, &version).await?;
+        }
+        cache_schema_version(version);
+        Ok(version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: PrefKey<bool> = PrefKey::new("key", 2, || false);
+
+    #[test]
+    fn test_check_schema_version() {
+        assert!(KEY.check_schema_version(1).is_err());
+        assert!(KEY.check_schema_version(2).is_ok());
+        assert!(KEY.check_schema_version(3).is_ok());
+    }
+
+    #[test]
+    fn test_schema_version_cache() {
+        cache_schema_version(3);
+        assert_eq!(cached_schema_version(), Some(3));
+        invalidate_schema_version("other");
+        assert_eq!(cached_schema_version(), Some(3));
+        invalidate_schema_version(SCHEMA_VERSION_KEY);
+        assert_eq!(cached_schema_version(), None);
+    }
+
+    #[test]
+    fn test_migration_order() {
+        let migrations = Migrations::new()
+            .with(Migration::new(3))
+            .with(Migration::new(1));
+        assert_eq!(migrations.latest_version(), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_duplicate_migration() {
+        let _ = Migrations::new()
+            .with(Migration::new(1).remove("a"))
+            .with(Migration::new(1).remove("b"));
+    }
+}
@@ -1,24 +1,75 @@
-use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, collections::BTreeMap};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
 use crate::{extern_functions::*, helpers::*};
 
-use crate::{error::Error};
+use crate::error::Error;
+use crate::preference_keys::{invalidate_schema_version, SCHEMA_VERSION_KEY};
+use crate::preferences_observer::{has_observers, is_observed, notify, PreferenceChange};
+
+/// The preferences group used if `Preferences::configure` has not been called.
+pub const DEFAULT_GROUP: &str = "CapacitorStorage";
+
+thread_local! {
+    static CONFIGURED_GROUP: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
 pub struct Preferences;
 impl Preferences {
     /// Configure the preferences plugin at runtime.
     pub async fn configure(options: impl Into<ConfigureOptions>) -> Result<(), Error> {
-        run_value_unit(options, preferences_configure).await
+        let options: ConfigureOptions = options.into();
+        let group = options.group.clone();
+        run_value_unit::<ConfigureOptions, _, _>(options, preferences_configure).await?;
+        CONFIGURED_GROUP.with(|x| *x.borrow_mut() = Some(group.clone()));
+        crate::preferences_cache::group_configured(&group);
+        Ok(())
+    }
+
+    /// The preferences group most recently set with `configure`.
+    pub fn configured_group() -> String {
+        CONFIGURED_GROUP.with(|x| x.borrow().clone())
+            .unwrap_or_else(|| DEFAULT_GROUP.to_string())
     }
 
     /// Set the value in preferences for a given key.
     pub async fn set(options: impl Into<SetOptions>) -> Result<(), Error> {
-        run_value_unit(options, preferences_set).await
+        let options: SetOptions = options.into();
+        invalidate_schema_version(&options.key);
+        if !is_observed(&options.key) {
+            return run_value_unit::<SetOptions, _, _>(options, preferences_set).await;
+        }
+
+        let old_value = Self::get(GetOptions::from(options.key.as_str())).await?.value;
+        run_value_unit::<SetOptions, _, _>(options.clone(), preferences_set).await?;
+        notify(PreferenceChange {
+            group: Self::configured_group(),
+            key: options.key,
+            old_value,
+            new_value: Some(options.value),
+        });
+        Ok(())
     }
 
     /// Remove the value from preferences for a given key, if any.
     pub async fn remove(options: impl Into<RemoveOptions>) -> Result<(), Error> {
-        run_value_unit(options, preferences_remove).await
+        let options: RemoveOptions = options.into();
+        invalidate_schema_version(&options.key);
+        if !is_observed(&options.key) {
+            return run_value_unit::<RemoveOptions, _, _>(options, preferences_remove).await;
+        }
+
+        let old_value = Self::get(GetOptions::from(options.key.as_str())).await?.value;
+        run_value_unit::<RemoveOptions, _, _>(options.clone(), preferences_remove).await?;
+        notify(PreferenceChange {
+            group: Self::configured_group(),
+            key: options.key,
+            old_value,
+            new_value: None,
+        });
+        Ok(())
     }
 
     /// Get the value from preferences of a given key.
@@ -28,13 +79,93 @@ impl Preferences {
 
     /// Clear keys and values from preferences.
     pub async fn clear() -> Result<(), Error> {
-        run_unit_unit(preferences_clear).await
+        invalidate_schema_version(SCHEMA_VERSION_KEY);
+        if !has_observers() {
+            return run_unit_unit(preferences_clear).await;
+        }
+
+        let keys: Vec<String> = Self::keys()
+            .await?
+            .keys
+            .into_iter()
+            .filter(|key| is_observed(key))
+            .collect();
+        // Resolve the old values first, so a clear which succeeds never returns an error
+        let old_values: Vec<Option<String>> =
+            futures::future::join_all(keys.iter().map(|key| Self::get(key.as_str())))
+                .await
+                .into_iter()
+                .map(|result| result.map(|x| x.value))
+                .collect::<Result<_, _>>()?;
+
+        run_unit_unit(preferences_clear).await?;
+
+        let group = Self::configured_group();
+        for (key, old_value) in keys.into_iter().zip(old_values) {
+            notify(PreferenceChange {
+                group: group.clone(),
+                key,
+                old_value,
+                new_value: None,
+            });
+        }
+        Ok(())
     }
 
     /// Return the list of known keys in preferences.
     pub async fn keys() -> Result<KeysResult, Error> {
         run_unit_value(preferences_keys).await
     }
+
+    /// Get every key and value in preferences.
+    pub async fn get_all() -> Result<BTreeMap<String, String>, Error> {
+        let keys = Self::keys().await?.keys;
+        let results =
+            futures::future::join_all(keys.iter().map(|key| Self::get(key.as_str()))).await;
+
+        let mut values = BTreeMap::new();
+        for (key, result) in keys.into_iter().zip(results) {
+            if let Some(value) = result?.value {
+                values.insert(key, value);
+            }
+        }
+        Ok(values)
+    }
+
+    /// Serialize a value as json and set it in preferences for a given key.
+    pub async fn set_json<T: Serialize>(key: &str, value: &T) -> Result<(), Error> {
+        let value = to_json(value)?;
+        Self::set(SetOptions {
+            key: key.to_string(),
+            value,
+        })
+        .await
+    }
+
+    /// Get the json value from preferences of a given key and deserialize it.
+    /// Returns `None` if there is no value for the key, or `Error::CorruptPreference` if the value cannot be deserialized.
+    pub async fn get_json<T: DeserializeOwned>(key: &str) -> Result<Option<T>, Error> {
+        let result = Self::get(GetOptions::from(key)).await?;
+        result
+            .value
+            .map(|value| from_json(key, &value))
+            .transpose()
+    }
+}
+
+pub(crate) fn to_json<T: Serialize>(value: &T) -> Result<String, Error> {
+    serde_json::to_string(value).map_err(|error| Error::JsonSerializeError {
+        typename: std::any::type_name::<T>(),
+        error,
+    })
+}
+
+pub(crate) fn from_json<T: DeserializeOwned>(key: &str, value: &str) -> Result<T, Error> {
+    serde_json::from_str(value).map_err(|error| Error::CorruptPreference {
+        key: key.to_string(),
+        typename: std::any::type_name::<T>(),
+        error,
+    })
 }
 
 #[skip_serializing_none]
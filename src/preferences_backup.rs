@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::preference_keys::is_reserved_key;
+use crate::preferences::{to_json, Preferences, RemoveOptions, SetOptions};
+
+/// Every key and value in a preferences group at a point in time.
+/// Serialize this to json to move settings between devices, e.g. with `Share` or `Clipboard`.
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PreferencesSnapshot {
+    /// The preferences group the snapshot was taken from.
+    pub group: String,
+    /// When the snapshot was taken, in milliseconds since the unix epoch.
+    pub timestamp: u64,
+    /// The keys and values in the group.
+    pub values: BTreeMap<String, String>,
+}
+
+impl PreferencesSnapshot {
+    /// Serialize this snapshot as json.
+    pub fn to_json(&self) -> Result<String, Error> {
+        to_json(self)
+    }
+
+    /// Deserialize a snapshot from json.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json).map_err(|error| Error::JsonDeserializeError {
+            typename: std::any::type_name::<Self>(),
+            error,
+        })
+    }
+}
+
+/// How to combine an imported snapshot with the existing preferences.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeStrategy {
+    /// Remove every key which is not in the snapshot, except reserved keys, then set every value in the snapshot.
+    Replace,
+    /// Set every value in the snapshot, keeping any other keys.
+    #[default]
+    Overwrite,
+    /// Only set values for keys which do not already exist.
+    KeepExisting,
+}
+
+impl Preferences {
+    /// Take a snapshot of every key and value in the currently configured group, except reserved keys such as the schema version.
+    pub async fn export() -> Result<PreferencesSnapshot, Error> {
+        Self::export_with(|key| !is_reserved_key(key)).await
+    }
+
+    /// Take a snapshot of the keys and values in the currently configured group for which `filter` returns true.
+    pub async fn export_with(filter: impl Fn(&str) -> bool) -> Result<PreferencesSnapshot, Error> {
+        let mut values = Self::get_all().await?;
+        values.retain(|key, _| filter(key));
+        Ok(PreferencesSnapshot {
+            group: Self::configured_group(),
+            timestamp: js_sys::Date::now() as u64,
+            values,
+        })
+    }
+
+    /// Restore a snapshot into the currently configured group, which need not be the group it was taken from.
+    pub async fn import(
+        snapshot: PreferencesSnapshot,
+        strategy: MergeStrategy,
+    ) -> Result<(), Error> {
+        let existing = Self::keys().await?.keys;
+
+        if strategy == MergeStrategy::Replace {
+            for key in existing
+                .iter()
+                .filter(|k| !is_reserved_key(k) && !snapshot.values.contains_key(*k))
+            {
+                Self::remove(RemoveOptions::from(key.as_str())).await?;
+            }
+        }
+
+        for (key, value) in snapshot.values {
+            if strategy == MergeStrategy::KeepExisting && existing.contains(&key) {
+                continue;
+            }
+            Self::set(SetOptions { key, value }).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let snapshot = PreferencesSnapshot {
+            group: "CapacitorStorage".to_string(),
+            timestamp: 1_700_000_000_000,
+            values: BTreeMap::from([
+                ("theme".to_string(), "\"dark\"".to_string()),
+                ("volume".to_string(), "0.5".to_string()),
+            ]),
+        };
+
+        let json = snapshot.to_json().unwrap();
+        assert_eq!(PreferencesSnapshot::from_json(&json).unwrap(), snapshot);
+
+        assert!(PreferencesSnapshot::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_reserved_keys() {
+        assert!(is_reserved_key(crate::preference_keys::SCHEMA_VERSION_KEY));
+        assert!(!is_reserved_key("theme"));
+    }
+}
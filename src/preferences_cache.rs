@@ -0,0 +1,316 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::app::App;
+use crate::error::Error;
+use crate::helpers::sleep;
+use crate::plugin_listener_handle::PluginListenerHandle;
+use crate::preference_keys::{PrefKey, SCHEMA_VERSION_KEY};
+use crate::preferences::{from_json, to_json, Preferences, RemoveOptions, SetOptions};
+
+/// An in-memory copy of a preferences group.
+/// Reads are served synchronously from memory.
+/// Writes are applied to memory immediately and saved to preferences in batches, after `flush_interval` has passed and whenever the app is paused.
+///
+/// Writes which fail are retried with an exponential backoff, up to `MAX_BACKOFF` times `flush_interval`.
+///
+/// The cache belongs to the group which was configured when it was loaded. If a different group is configured when the cache is flushed, the flush fails
+/// and the writes are kept until the cache's group is configured again or `flush` is called explicitly.
+#[derive(Clone, Debug)]
+pub struct PreferencesCache {
+    state: Rc<RefCell<CacheState>>,
+}
+
+/// The largest multiple of `flush_interval` to wait before retrying failed writes.
+pub const MAX_BACKOFF: u32 = 64;
+
+thread_local! {
+    /// Every loaded cache, so they can be flushed when their group is configured again.
+    static CACHES: RefCell<Vec<Weak<RefCell<CacheState>>>> = const { RefCell::new(vec![]) };
+}
+
+/// Flush the caches which belong to a group which has just been configured.
+pub(crate) fn group_configured(group: &str) {
+    let caches: Vec<PreferencesCache> = CACHES.with(|caches| {
+        let mut caches = caches.borrow_mut();
+        caches.retain(|x| x.strong_count() > 0);
+        caches
+            .iter()
+            .filter_map(PreferencesCache::upgrade)
+            .collect()
+    });
+    for cache in caches {
+        let matches = {
+            let state = cache.state.borrow();
+            state.group == group && !state.pending.is_empty()
+        };
+        if matches {
+            cache.schedule_flush();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CacheState {
+    group: String,
+    values: BTreeMap<String, String>,
+    /// Writes which have not been saved yet. `None` means the key should be removed.
+    pending: BTreeMap<String, Option<String>>,
+    flush_interval: Duration,
+    flush_scheduled: bool,
+    /// The number of flushes in a row in which some writes failed.
+    failures: u32,
+    pause_listener: Option<PluginListenerHandle>,
+}
+
+impl CacheState {
+    /// How long to wait before flushing, backing off after failures.
+    fn flush_delay(&self) -> Duration {
+        let factor = 2u32.saturating_pow(self.failures).min(MAX_BACKOFF);
+        self.flush_interval.saturating_mul(factor)
+    }
+}
+
+impl Drop for CacheState {
+    fn drop(&mut self) {
+        if let Some(listener) = self.pause_listener.take() {
+            wasm_bindgen_futures::spawn_local(async move {
+                let _ = listener.remove_async().await;
+            });
+        }
+    }
+}
+
+impl PreferencesCache {
+    /// Load every key and value in the currently configured group.
+    pub async fn load(flush_interval: Duration) -> Result<Self, Error> {
+        let group = Preferences::configured_group();
+        let values = Preferences::get_all().await?;
+
+        let state = Rc::new(RefCell::new(CacheState {
+            group,
+            values,
+            pending: Default::default(),
+            flush_interval,
+            flush_scheduled: false,
+            failures: 0,
+            pause_listener: None,
+        }));
+
+        let weak = Rc::downgrade(&state);
+        let pause_listener = App::add_pause_listener(move |()| {
+            if let Some(cache) = Self::upgrade(&weak) {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let _ = cache.flush().await;
+                });
+            }
+        })
+        .await?;
+        state.borrow_mut().pause_listener = Some(pause_listener);
+        CACHES.with(|caches| caches.borrow_mut().push(Rc::downgrade(&state)));
+
+        Ok(Self { state })
+    }
+
+    fn upgrade(weak: &Weak<RefCell<CacheState>>) -> Option<Self> {
+        weak.upgrade().map(|state| Self { state })
+    }
+
+    /// The preferences group this cache belongs to.
+    pub fn group(&self) -> String {
+        self.state.borrow().group.clone()
+    }
+
+    /// The known keys.
+    pub fn keys(&self) -> Vec<String> {
+        self.state.borrow().values.keys().cloned().collect()
+    }
+
+    /// Get the value of a given key.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.state.borrow().values.get(key).cloned()
+    }
+
+    /// Get the json value of a given key and deserialize it.
+    pub fn get_json<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, Error> {
+        self.get(key)
+            .map(|value| from_json(key, &value))
+            .transpose()
+    }
+
+    /// Get the value for a typed key, or its default value if nothing is stored.
+    /// Returns an error if the stored schema version is older than the key's version.
+    pub fn get_key<T: DeserializeOwned>(&self, key: &PrefKey<T>) -> Result<T, Error> {
+        self.check_schema_version(key)?;
+        Ok(self
+            .get_json(key.name)?
+            .unwrap_or_else(|| key.default_value()))
+    }
+
+    /// Set the value for a given key.
+    pub fn set(&self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        let value = value.into();
+        {
+            let mut state = self.state.borrow_mut();
+            state.values.insert(key.clone(), value.clone());
+            state.pending.insert(key, Some(value));
+        }
+        self.schedule_flush();
+    }
+
+    /// Serialize a value as json and set it for a given key.
+    pub fn set_json<T: Serialize>(&self, key: impl Into<String>, value: &T) -> Result<(), Error> {
+        self.set(key, to_json(value)?);
+        Ok(())
+    }
+
+    /// Set the value for a typed key.
+    /// Returns an error if the stored schema version is older than the key's version.
+    pub fn set_key<T: Serialize>(&self, key: &PrefKey<T>, value: &T) -> Result<(), Error> {
+        self.check_schema_version(key)?;
+        self.set_json(key.name, value)
+    }
+
+    fn check_schema_version<T>(&self, key: &PrefKey<T>) -> Result<(), Error> {
+        if key.version == 0 {
+            return Ok(());
+        }
+        let schema_version = self.get_json(SCHEMA_VERSION_KEY)?.unwrap_or_default();
+        key.check_schema_version(schema_version)
+    }
+
+    /// Remove the value for a given key, if any.
+    pub fn remove(&self, key: impl Into<String>) {
+        let key = key.into();
+        {
+            let mut state = self.state.borrow_mut();
+            state.values.remove(&key);
+            state.pending.insert(key, None);
+        }
+        self.schedule_flush();
+    }
+
+    /// Whether there are writes which have not been saved yet.
+    pub fn has_pending_writes(&self) -> bool {
+        !self.state.borrow().pending.is_empty()
+    }
+
+    fn schedule_flush(&self) {
+        let delay = {
+            let mut state = self.state.borrow_mut();
+            if state.flush_scheduled {
+                return;
+            }
+            state.flush_scheduled = true;
+            state.flush_delay()
+        };
+
+        let weak = Rc::downgrade(&self.state);
+        wasm_bindgen_futures::spawn_local(async move {
+            sleep(delay).await;
+            if let Some(cache) = Self::upgrade(&weak) {
+                let _ = cache.flush().await;
+            }
+        });
+    }
+
+    /// Save all pending writes to preferences now.
+    /// Writes which fail are kept and another flush is scheduled to retry them, after a longer delay each time.
+    /// If a different group is configured, the writes are kept but no flush is scheduled.
+    pub async fn flush(&self) -> Result<(), Error> {
+        let (group, pending) = {
+            let mut state = self.state.borrow_mut();
+            state.flush_scheduled = false;
+            (state.group.clone(), std::mem::take(&mut state.pending))
+        };
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let configured = Preferences::configured_group();
+        if configured != group {
+            self.requeue(pending);
+            return Err(Error::PreferencesGroupMismatch { group, configured });
+        }
+
+        let results = futures::future::join_all(pending.iter().map(|(key, value)| async move {
+            match value {
+                Some(value) => {
+                    Preferences::set(SetOptions {
+                        key: key.clone(),
+                        value: value.clone(),
+                    })
+                    .await
+                }
+                None => Preferences::remove(RemoveOptions::from(key.as_str())).await,
+            }
+        }))
+        .await;
+
+        let mut failed = BTreeMap::new();
+        let mut first_error = None;
+        for ((key, value), result) in pending.into_iter().zip(results) {
+            if let Err(error) = result {
+                failed.insert(key, value);
+                first_error.get_or_insert(error);
+            }
+        }
+        let any_failed = !failed.is_empty();
+        self.requeue(failed);
+        {
+            let mut state = self.state.borrow_mut();
+            state.failures = if any_failed {
+                state.failures.saturating_add(1)
+            } else {
+                0
+            };
+        }
+        if any_failed {
+            self.schedule_flush();
+        }
+
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn requeue(&self, writes: BTreeMap<String, Option<String>>) {
+        let mut state = self.state.borrow_mut();
+        for (key, value) in writes {
+            // Don't overwrite anything written since the flush started
+            state.pending.entry(key).or_insert(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preferences::DEFAULT_GROUP;
+
+    #[test]
+    fn test_flush_delay() {
+        let mut state = CacheState {
+            group: DEFAULT_GROUP.to_string(),
+            values: Default::default(),
+            pending: Default::default(),
+            flush_interval: Duration::from_secs(1),
+            flush_scheduled: false,
+            failures: 0,
+            pause_listener: None,
+        };
+        assert_eq!(state.flush_delay(), Duration::from_secs(1));
+        state.failures = 3;
+        assert_eq!(state.flush_delay(), Duration::from_secs(8));
+        state.failures = 100;
+        assert_eq!(state.flush_delay(), Duration::from_secs(MAX_BACKOFF as u64));
+    }
+}
@@ -0,0 +1,171 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::preferences::Preferences;
+
+/// A change made to a preference through `Preferences::set`, `Preferences::remove` or `Preferences::clear`.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct PreferenceChange {
+    /// The preferences group the change was made in.
+    pub group: String,
+    /// The key whose value changed.
+    pub key: String,
+    /// The value before the change, if any.
+    pub old_value: Option<String>,
+    /// The value after the change, if any.
+    pub new_value: Option<String>,
+}
+
+/// Which preference keys a change listener is interested in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ObservedKey {
+    /// Only changes to this key.
+    Key(String),
+    /// Changes to any key.
+    Any,
+}
+
+impl ObservedKey {
+    fn matches(&self, key: &str) -> bool {
+        match self {
+            ObservedKey::Key(k) => k == key,
+            ObservedKey::Any => true,
+        }
+    }
+}
+
+impl From<&str> for ObservedKey {
+    fn from(value: &str) -> Self {
+        ObservedKey::Key(value.to_string())
+    }
+}
+
+impl From<String> for ObservedKey {
+    fn from(value: String) -> Self {
+        ObservedKey::Key(value)
+    }
+}
+
+type Callback = Rc<dyn Fn(&PreferenceChange)>;
+
+#[derive(Default)]
+struct Observers {
+    next_id: u64,
+    observers: Vec<(u64, ObservedKey, Callback)>,
+}
+
+thread_local! {
+    static OBSERVERS: RefCell<Observers> = RefCell::new(Observers::default());
+}
+
+/// A handle for a preferences change listener.
+/// If this is dropped, the listener will be removed, so either store it somewhere or call `leak`.
+#[derive(Debug, PartialEq, Eq)]
+#[must_use = "Handle must not be dropped unless the listener is no longer needed"]
+pub struct PreferenceListenerHandle {
+    id: u64,
+}
+
+impl PreferenceListenerHandle {
+    /// Leak this listener so it will never be removed
+    pub fn leak(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for PreferenceListenerHandle {
+    fn drop(&mut self) {
+        let _ = OBSERVERS.try_with(|x| {
+            x.borrow_mut().observers.retain(|(id, ..)| *id != self.id);
+        });
+    }
+}
+
+impl Preferences {
+    /// Listen for changes to a preference key, or to any key.
+    /// The listener is called whenever a value is written through this crate's `Preferences::set`, `Preferences::remove` or `Preferences::clear`.
+    /// Changes made by other means (e.g. javascript or native code) are not observed.
+    pub fn add_change_listener<F: Fn(&PreferenceChange) + 'static>(
+        key: impl Into<ObservedKey>,
+        func: F,
+    ) -> PreferenceListenerHandle {
+        let key = key.into();
+        OBSERVERS.with(|x| {
+            let mut observers = x.borrow_mut();
+            let id = observers.next_id;
+            observers.next_id += 1;
+            observers.observers.push((id, key, Rc::new(func)));
+            PreferenceListenerHandle { id }
+        })
+    }
+}
+
+/// Whether any listener is interested in changes to this key.
+pub(crate) fn is_observed(key: &str) -> bool {
+    OBSERVERS.with(|x| x.borrow().observers.iter().any(|(_, k, _)| k.matches(key)))
+}
+
+/// Whether there are any listeners at all.
+pub(crate) fn has_observers() -> bool {
+    OBSERVERS.with(|x| !x.borrow().observers.is_empty())
+}
+
+pub(crate) fn notify(change: PreferenceChange) {
+    // Collect the callbacks first so they can add or remove listeners
+    let callbacks: Vec<Callback> = OBSERVERS.with(|x| {
+        x.borrow()
+            .observers
+            .iter()
+            .filter(|(_, k, _)| k.matches(&change.key))
+            .map(|(.., callback)| callback.clone())
+            .collect()
+    });
+
+    for callback in callbacks {
+        callback(&change);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notify() {
+        let changes: Rc<RefCell<Vec<String>>> = Default::default();
+
+        let c = changes.clone();
+        let key_handle = Preferences::add_change_listener("volume", move |change| {
+            c.borrow_mut().push(format!("volume:{}", change.key))
+        });
+        let c = changes.clone();
+        let any_handle = Preferences::add_change_listener(ObservedKey::Any, move |change| {
+            c.borrow_mut().push(format!("any:{}", change.key))
+        });
+
+        assert!(is_observed("volume"));
+        assert!(is_observed("theme"));
+
+        notify(PreferenceChange {
+            key: "volume".to_string(),
+            ..Default::default()
+        });
+        drop(any_handle);
+        notify(PreferenceChange {
+            key: "theme".to_string(),
+            ..Default::default()
+        });
+        notify(PreferenceChange {
+            key: "volume".to_string(),
+            ..Default::default()
+        });
+
+        assert!(!is_observed("theme"));
+        drop(key_handle);
+        assert!(!has_observers());
+
+        assert_eq!(
+            changes.borrow().as_slice(),
+            ["volume:volume", "any:volume", "volume:volume"]
+        );
+    }
+}
@@ -3,6 +3,7 @@ use crate::error::Error;
 #[cfg(any(feature = "ios", feature = "android"))]
 use crate::helpers::*;
 use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
 
 #[cfg(any(feature = "ios", feature = "android"))]
 use crate::extern_functions::*;
@@ -15,13 +16,31 @@ impl StatusBar {
     #[cfg(any(feature = "ios", feature = "android"))]
     /// Show the status bar. On iOS, if the status bar is initially hidden and the initial style is set to UIStatusBarStyleLightContent, first show call might present a glitch on the animation showing the text as dark and then transition to light. It's recommended to use Animation.None as the animation on the first call.
     pub async fn show() -> Result<(), Error> {
-        run_unit_unit(status_bar_show).await
+        Self::show_with_options(AnimationOptions::default()).await
+    }
+
+    #[cfg(any(feature = "ios", feature = "android"))]
+    /// Show the status bar with the given animation.
+    pub async fn show_with_options(options: impl Into<AnimationOptions>) -> Result<(), Error> {
+        run_value_unit(options, status_bar_show).await
     }
 
     #[cfg(any(feature = "ios", feature = "android"))]
     /// Hide the status bar.
     pub async fn hide() -> Result<(), Error> {
-        run_unit_unit(status_bar_hide).await
+        Self::hide_with_options(AnimationOptions::default()).await
+    }
+
+    #[cfg(any(feature = "ios", feature = "android"))]
+    /// Hide the status bar with the given animation.
+    pub async fn hide_with_options(options: impl Into<AnimationOptions>) -> Result<(), Error> {
+        run_value_unit(options, status_bar_hide).await
+    }
+
+    #[cfg(any(feature = "ios", feature = "android"))]
+    /// Get info about the current state of the status bar.
+    pub async fn get_info() -> Result<StatusBarInfo, Error> {
+        run_unit_value(status_bar_get_info).await
     }
 
     #[cfg(any(feature = "ios", feature = "android"))]
@@ -45,6 +64,62 @@ impl StatusBar {
     ) -> Result<(), Error> {
         run_value_unit(options, status_bar_set_overlays_web_view).await
     }
+
+    #[cfg(feature = "ios")]
+    /// Listen for when the status bar is shown or hidden.
+    /// This event is only supported on iOS.
+    pub async fn add_visibility_changed_listener<F: Fn(StatusBarInfo) + 'static>(
+        func: F,
+    ) -> Result<crate::plugin_listener_handle::PluginListenerHandle, Error> {
+        listen_async(func, "statusBarVisibilityChanged", status_bar_add_listener).await
+    }
+
+    #[cfg(feature = "ios")]
+    /// Listen for when the status bar overlay is changed.
+    /// This event is only supported on iOS.
+    pub async fn add_overlay_changed_listener<F: Fn(StatusBarInfo) + 'static>(
+        func: F,
+    ) -> Result<crate::plugin_listener_handle::PluginListenerHandle, Error> {
+        listen_async(func, "statusBarOverlayChanged", status_bar_add_listener).await
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusBarInfo {
+    /// Whether the status bar is visible or not.
+    pub visible: bool,
+    /// The current status bar style.
+    pub style: Style,
+    /// The current status bar color. Only available on Android.
+    pub color: Option<String>,
+    /// Whether the status bar is overlaid or not. Only available on Android.
+    pub overlays: Option<bool>,
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnimationOptions {
+    /// The type of status bar animation used when showing or hiding. This option is only supported on iOS.
+    pub animation: Animation,
+}
+
+impl From<Animation> for AnimationOptions {
+    fn from(animation: Animation) -> Self {
+        Self { animation }
+    }
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Animation {
+    /// No animation during show/hide.
+    None,
+    /// Slide animation during show/hide. It doesn't work on iOS 15+.
+    Slide,
+    #[default]
+    /// Fade animation during show/hide.
+    Fade,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
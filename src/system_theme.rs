@@ -0,0 +1,213 @@
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::device::{Device, Platform};
+use crate::error::Error;
+use crate::extern_functions::{match_media, MediaQueryList};
+#[cfg(feature = "safe_area_plugin")]
+use crate::safe_area::{Config, LightOrDark, Options, SafeArea};
+#[cfg(any(feature = "ios", feature = "android"))]
+use crate::status_bar::StatusBar;
+use crate::status_bar::Style;
+
+const DARK_MODE_QUERY: &str = "(prefers-color-scheme: dark)";
+
+/// Whether a palette has light or dark backgrounds.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub enum Appearance {
+    #[default]
+    Light,
+    Dark,
+}
+
+/// The colors of the system bars.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThemePalette {
+    /// Whether the bar backgrounds are light or dark. Bar content will use the opposite.
+    pub appearance: Appearance,
+    /// The background color of the status bar, in the format #RRGGBB or #AARRGGBB. Ignored on iOS.
+    pub status_bar_color: String,
+    /// The background color of the navigation bar, in the format #RRGGBB or #AARRGGBB. Only used on Android with the `safe_area_plugin` feature.
+    pub navigation_bar_color: String,
+}
+
+impl ThemePalette {
+    /// White bars with dark content.
+    pub fn light() -> Self {
+        Self {
+            appearance: Appearance::Light,
+            status_bar_color: "#FFFFFF".to_string(),
+            navigation_bar_color: "#FFFFFF".to_string(),
+        }
+    }
+
+    /// Black bars with light content.
+    pub fn dark() -> Self {
+        Self {
+            appearance: Appearance::Dark,
+            status_bar_color: "#000000".to_string(),
+            navigation_bar_color: "#000000".to_string(),
+        }
+    }
+
+    /// The status bar style for this palette.
+    pub fn status_bar_style(&self) -> Style {
+        match self.appearance {
+            Appearance::Light => Style::Light,
+            Appearance::Dark => Style::Dark,
+        }
+    }
+
+    #[cfg(feature = "safe_area_plugin")]
+    /// The safe area config for this palette.
+    pub fn safe_area_config(&self) -> Config {
+        let content = match self.appearance {
+            Appearance::Light => LightOrDark::Dark,
+            Appearance::Dark => LightOrDark::Light,
+        };
+        Config {
+            custom_colors_for_system_bars: true,
+            status_bar_color: self.status_bar_color.clone(),
+            status_bar_content: content,
+            navigation_bar_color: self.navigation_bar_color.clone(),
+            navigation_bar_content: content,
+            ..Default::default()
+        }
+    }
+}
+
+/// Applies a single palette to the status bar and, with the `safe_area_plugin` feature, the safe area system bars.
+///
+/// On Android the style and colors of the status bar are set, as well as the navigation bar colors if the safe area plugin is enabled.
+/// On iOS the status bar style is set, as well as the system bar content if the safe area plugin is enabled. The status bar has no background of its own on iOS.
+/// On web nothing is changed.
+pub struct SystemTheme;
+
+impl SystemTheme {
+    /// Apply a palette on the current platform.
+    pub async fn apply(palette: &ThemePalette) -> Result<(), Error> {
+        let platform = Device::get_info().await?.platform;
+        Self::apply_for_platform(palette, platform).await
+    }
+
+    #[cfg_attr(
+        not(any(feature = "ios", feature = "android", feature = "safe_area_plugin")),
+        allow(unused_variables)
+    )]
+    async fn apply_for_platform(palette: &ThemePalette, platform: Platform) -> Result<(), Error> {
+        match platform {
+            Platform::Web => {}
+            Platform::IOs => {
+                #[cfg(any(feature = "ios", feature = "android"))]
+                StatusBar::set_style(palette.status_bar_style()).await?;
+                #[cfg(feature = "safe_area_plugin")]
+                SafeArea::enable(Options {
+                    config: palette.safe_area_config(),
+                })?;
+            }
+            Platform::Android => {
+                #[cfg(any(feature = "ios", feature = "android"))]
+                {
+                    StatusBar::set_style(palette.status_bar_style()).await?;
+                    StatusBar::set_background_color(palette.status_bar_color.as_str()).await?;
+                }
+                #[cfg(feature = "safe_area_plugin")]
+                SafeArea::enable(Options {
+                    config: palette.safe_area_config(),
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the OS is currently using dark mode, according to the `prefers-color-scheme` media query.
+    pub fn prefers_dark() -> bool {
+        match_media(DARK_MODE_QUERY).matches()
+    }
+
+    /// Apply the light or dark palette to match the OS dark mode setting, and again whenever that setting changes.
+    pub async fn follow_system(
+        light: ThemePalette,
+        dark: ThemePalette,
+    ) -> Result<SystemThemeListener, Error> {
+        let platform = Device::get_info().await?.platform;
+        let query = match_media(DARK_MODE_QUERY);
+        let palettes = Rc::new((light, dark));
+
+        let initial = if query.matches() {
+            &palettes.1
+        } else {
+            &palettes.0
+        };
+        Self::apply_for_platform(initial, platform).await?;
+
+        let closure = Closure::new(move |event: JsValue| {
+            let is_dark = js_sys::Reflect::get(&event, &"matches".into())
+                .ok()
+                .and_then(|x| x.as_bool())
+                .unwrap_or_default();
+            let palettes = palettes.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let palette = if is_dark { &palettes.1 } else { &palettes.0 };
+                let _ = Self::apply_for_platform(palette, platform).await;
+            });
+        });
+        query.add_event_listener("change", &closure);
+
+        Ok(SystemThemeListener { query, closure })
+    }
+}
+
+/// A handle for following the OS dark mode setting.
+/// If this is dropped, the theme will stop following the setting, so either store it somewhere or call `leak`.
+#[must_use = "Handle must not be dropped unless the theme should stop following the OS setting"]
+pub struct SystemThemeListener {
+    query: MediaQueryList,
+    closure: Closure<dyn Fn(JsValue)>,
+}
+
+impl std::fmt::Debug for SystemThemeListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SystemThemeListener").finish()
+    }
+}
+
+impl SystemThemeListener {
+    /// Leak this listener so the theme will always follow the OS setting
+    pub fn leak(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for SystemThemeListener {
+    fn drop(&mut self) {
+        self.query.remove_event_listener("change", &self.closure);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_bar_style() {
+        assert_eq!(ThemePalette::light().status_bar_style(), Style::Light);
+        assert_eq!(ThemePalette::dark().status_bar_style(), Style::Dark);
+    }
+
+    #[cfg(feature = "safe_area_plugin")]
+    #[test]
+    fn test_safe_area_config() {
+        let config = ThemePalette::dark().safe_area_config();
+        assert!(config.custom_colors_for_system_bars);
+        assert_eq!(config.status_bar_color, "#000000");
+        assert_eq!(config.status_bar_content, LightOrDark::Light);
+        assert_eq!(config.navigation_bar_color, "#000000");
+        assert_eq!(config.navigation_bar_content, LightOrDark::Light);
+
+        let config = ThemePalette::light().safe_area_config();
+        assert_eq!(config.status_bar_content, LightOrDark::Dark);
+        assert_eq!(config.navigation_bar_content, LightOrDark::Dark);
+    }
+}
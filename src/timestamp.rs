@@ -0,0 +1,483 @@
+use std::{
+    fmt::Display,
+    ops::{Add, Sub},
+    str::FromStr,
+    time::Duration,
+};
+
+use serde::{de::Visitor, Deserialize, Serialize};
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+/// A point in time, stored as milliseconds since the unix epoch in UTC.
+///
+/// Serializes as an ISO 8601 string with millisecond precision (e.g. `2024-03-01T09:30:00.000Z`).
+/// Deserializes from an ISO 8601 string with a `Z` or numeric offset, or from a number of milliseconds since the unix epoch.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    millis: i64,
+}
+
+/// A date and time broken into its parts.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CivilDateTime {
+    pub year: i32,
+    /// 1 to 12
+    pub month: u32,
+    /// 1 to 31
+    pub day: u32,
+    /// 0 to 23
+    pub hour: u32,
+    /// 0 to 59
+    pub minute: u32,
+    /// 0 to 59
+    pub second: u32,
+    /// 0 to 999
+    pub millisecond: u32,
+}
+
+impl CivilDateTime {
+    /// The day of the week, from 1 (Sunday) to 7 (Saturday).
+    pub fn weekday(&self) -> u32 {
+        let days = days_from_civil(self.year as i64, self.month, self.day);
+        // 1970-01-01 was a Thursday
+        ((days + 4).rem_euclid(7) + 1) as u32
+    }
+}
+
+impl Timestamp {
+    pub const UNIX_EPOCH: Self = Self { millis: 0 };
+
+    pub const fn from_millis(millis: i64) -> Self {
+        Self { millis }
+    }
+
+    /// Milliseconds since the unix epoch.
+    pub const fn as_millis(&self) -> i64 {
+        self.millis
+    }
+
+    /// The current time.
+    #[cfg(target_arch = "wasm32")]
+    pub fn now() -> Self {
+        Self::from_millis(js_sys::Date::now() as i64)
+    }
+
+    /// The current time.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn now() -> Self {
+        let millis = match std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+            Ok(d) => d.as_millis() as i64,
+            Err(e) => -(e.duration().as_millis() as i64),
+        };
+        Self::from_millis(millis)
+    }
+
+    /// Create from a UTC date and time.
+    /// Returns `None` if any part is out of range.
+    pub fn from_civil(civil: CivilDateTime) -> Option<Self> {
+        let CivilDateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            millisecond,
+        } = civil;
+        if !(1..=12).contains(&month)
+            || day < 1
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+            || millisecond > 999
+        {
+            return None;
+        }
+
+        let days = days_from_civil(year as i64, month, day);
+        let millis =
+            (((hour as i64 * 60 + minute as i64) * 60 + second as i64) * 1000) + millisecond as i64;
+        days.checked_mul(MILLIS_PER_DAY)?
+            .checked_add(millis)
+            .map(Self::from_millis)
+    }
+
+    /// Break this into a UTC date and time.
+    pub fn to_civil(&self) -> CivilDateTime {
+        let days = self.millis.div_euclid(MILLIS_PER_DAY);
+        let time = self.millis.rem_euclid(MILLIS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+
+        CivilDateTime {
+            year: year as i32,
+            month,
+            day,
+            hour: (time / 3_600_000) as u32,
+            minute: (time / 60_000 % 60) as u32,
+            second: (time / 1000 % 60) as u32,
+            millisecond: (time % 1000) as u32,
+        }
+    }
+
+    /// Add a duration, returning `None` on overflow.
+    pub fn checked_add(&self, duration: Duration) -> Option<Self> {
+        let millis = i64::try_from(duration.as_millis()).ok()?;
+        self.millis.checked_add(millis).map(Self::from_millis)
+    }
+
+    /// Subtract a duration, returning `None` on overflow.
+    pub fn checked_sub(&self, duration: Duration) -> Option<Self> {
+        let millis = i64::try_from(duration.as_millis()).ok()?;
+        self.millis.checked_sub(millis).map(Self::from_millis)
+    }
+
+    /// Create a javascript `Date` at this time.
+    pub fn to_js_date(&self) -> js_sys::Date {
+        js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(self.millis as f64))
+    }
+
+    /// Format as an ISO 8601 string in UTC with millisecond precision.
+    pub fn to_iso_string(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, rhs: Duration) -> Self::Output {
+        self.checked_add(rhs)
+            .expect("overflow when adding duration to timestamp")
+    }
+}
+
+impl Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, rhs: Duration) -> Self::Output {
+        self.checked_sub(rhs)
+            .expect("overflow when subtracting duration from timestamp")
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = self.to_civil();
+        if (0..=9999).contains(&c.year) {
+            write!(f, "{:04}", c.year)?;
+        } else {
+            write!(f, "{:+07}", c.year)?;
+        }
+        write!(
+            f,
+            "-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            c.month, c.day, c.hour, c.minute, c.second, c.millisecond
+        )
+    }
+}
+
+/// The error returned when a string is not a valid ISO 8601 date and time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseTimestampError {
+    pub value: String,
+}
+
+impl Display for ParseTimestampError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' is not a valid ISO 8601 date and time", self.value)
+    }
+}
+
+impl std::error::Error for ParseTimestampError {}
+
+impl FromStr for Timestamp {
+    type Err = ParseTimestampError;
+
+    /// Parse an ISO 8601 date and time such as `2024-03-01T09:30:00Z`, `2024-03-01T09:30:00.123+01:00` or `2024-03-01`.
+    /// Times without an offset are treated as UTC.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_iso(s.trim()).ok_or_else(|| ParseTimestampError {
+            value: s.to_string(),
+        })
+    }
+}
+
+fn parse_iso(s: &str) -> Option<Timestamp> {
+    fn number<T: FromStr>(s: &str, len: usize) -> Option<T> {
+        if s.len() != len || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        s.parse().ok()
+    }
+
+    let (date, time) = match s.split_once(['T', 't', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (s, None),
+    };
+
+    let (year, rest) = match date.strip_prefix(['+', '-']) {
+        Some(rest) => {
+            let (year, rest) = rest.split_at(rest.find('-')?);
+            let year: i32 = number(year, 6)?;
+            (if date.starts_with('-') { -year } else { year }, rest)
+        }
+        None => {
+            let (year, rest) = date.split_at(date.find('-')?);
+            (number(year, 4)?, rest)
+        }
+    };
+    let (month, day) = rest.strip_prefix('-')?.split_once('-')?;
+
+    let mut civil = CivilDateTime {
+        year,
+        month: number(month, 2)?,
+        day: number(day, 2)?,
+        ..Default::default()
+    };
+
+    let mut offset_minutes = 0i64;
+    if let Some(time) = time {
+        let (time, offset) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+            (time, None)
+        } else if let Some(index) = time.rfind(['+', '-']) {
+            (&time[..index], Some(&time[index..]))
+        } else {
+            (time, None)
+        };
+
+        if let Some(offset) = offset {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let offset = &offset[1..];
+            let (hours, minutes) = offset
+                .split_once(':')
+                .unwrap_or(offset.split_at(2.min(offset.len())));
+            let hours: i64 = number(hours, 2)?;
+            let minutes: i64 = if minutes.is_empty() {
+                0
+            } else {
+                number(minutes, 2)?
+            };
+            offset_minutes = sign * (hours * 60 + minutes);
+        }
+
+        let (time, fraction) = match time.split_once(['.', ',']) {
+            Some((time, fraction)) => (time, Some(fraction)),
+            None => (time, None),
+        };
+        let mut parts = time.split(':');
+        civil.hour = number(parts.next()?, 2)?;
+        civil.minute = number(parts.next()?, 2)?;
+        civil.second = match parts.next() {
+            Some(second) => number(second, 2)?,
+            None => 0,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        if let Some(fraction) = fraction {
+            if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            let digits: String = fraction.chars().chain("00".chars()).take(3).collect();
+            civil.millisecond = digits.parse().ok()?;
+        }
+    }
+
+    let timestamp = Timestamp::from_civil(civil)?;
+    timestamp
+        .as_millis()
+        .checked_sub(offset_minutes * 60_000)
+        .map(Timestamp::from_millis)
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(TimestampVisitor)
+    }
+}
+
+struct TimestampVisitor;
+
+impl<'de> Visitor<'de> for TimestampVisitor {
+    type Value = Timestamp;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter
+            .write_str("an ISO 8601 date string or a number of milliseconds since the unix epoch")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(Timestamp::from_millis(v))
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        i64::try_from(v)
+            .map(Timestamp::from_millis)
+            .map_err(|_| E::custom("timestamp out of range"))
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        if !v.is_finite() || v.abs() >= i64::MAX as f64 {
+            return Err(E::custom("timestamp out of range"));
+        }
+        Ok(Timestamp::from_millis(v.round() as i64))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for Timestamp {
+    fn from(value: chrono::DateTime<Tz>) -> Self {
+        Self::from_millis(value.timestamp_millis())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp {
+    /// Convert to a chrono `DateTime`. Returns `None` if this is outside chrono's supported range.
+    pub fn to_chrono(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::from_timestamp_millis(self.millis)
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for Timestamp {
+    fn from(value: time::OffsetDateTime) -> Self {
+        Self::from_millis((value.unix_timestamp_nanos() / 1_000_000) as i64)
+    }
+}
+
+#[cfg(feature = "time")]
+impl Timestamp {
+    /// Convert to a time `OffsetDateTime` in UTC. Returns `None` if this is outside time's supported range.
+    pub fn to_offset_date_time(&self) -> Option<time::OffsetDateTime> {
+        time::OffsetDateTime::from_unix_timestamp_nanos(self.millis as i128 * 1_000_000).ok()
+    }
+}
+
+pub(crate) fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+pub(crate) fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+/// The number of days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+/// See <http://howardhinnant.github.io/date_algorithms.html>
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The date in the proleptic Gregorian calendar of a number of days since 1970-01-01.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_round_trip() {
+        for millis in [
+            0,
+            951_782_400_000,
+            1_709_285_400_123,
+            -1,
+            -62_135_596_800_000,
+            253_402_300_799_999,
+        ] {
+            let timestamp = Timestamp::from_millis(millis);
+            assert_eq!(Timestamp::from_civil(timestamp.to_civil()), Some(timestamp));
+        }
+
+        let civil = Timestamp::from_millis(951_782_400_000).to_civil();
+        assert_eq!((civil.year, civil.month, civil.day), (2000, 2, 29));
+        assert_eq!(civil.weekday(), 3);
+        assert_eq!(Timestamp::UNIX_EPOCH.to_civil().weekday(), 5);
+    }
+
+    #[test]
+    fn test_iso() {
+        let timestamp = Timestamp::from_millis(1_709_285_400_123);
+        assert_eq!(timestamp.to_string(), "2024-03-01T09:30:00.123Z");
+        assert_eq!(
+            Timestamp::from_millis(-1).to_string(),
+            "1969-12-31T23:59:59.999Z"
+        );
+
+        for s in [
+            "2024-03-01T09:30:00.123Z",
+            "2024-03-01T10:30:00.123+01:00",
+            "2024-03-01T04:00:00.123-0530",
+            "2024-03-01 09:30:00.123456",
+        ] {
+            assert_eq!(s.parse(), Ok(timestamp), "{s}");
+        }
+        assert_eq!(
+            "2024-03-01".parse(),
+            Ok(Timestamp::from_millis(1_709_251_200_000))
+        );
+
+        for s in [
+            "",
+            "2024-02-30T00:00:00Z",
+            "2024-03-01T25:00:00Z",
+            "2024-3-1",
+            "yesterday",
+        ] {
+            assert!(s.parse::<Timestamp>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_serde() {
+        let timestamp = Timestamp::from_millis(1_709_285_400_123);
+        let json = serde_json::to_string(&timestamp).unwrap();
+        assert_eq!(json, "\"2024-03-01T09:30:00.123Z\"");
+        assert_eq!(serde_json::from_str::<Timestamp>(&json).unwrap(), timestamp);
+        assert_eq!(
+            serde_json::from_str::<Timestamp>("1709285400123").unwrap(),
+            timestamp
+        );
+        assert_eq!(
+            serde_json::from_str::<Timestamp>("1709285400123.0").unwrap(),
+            timestamp
+        );
+    }
+}